@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE urls DROP COLUMN IF EXISTS expires_at, DROP COLUMN IF EXISTS max_clicks;
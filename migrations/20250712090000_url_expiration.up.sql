@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE urls
+    ADD COLUMN expires_at TIMESTAMPTZ,
+    ADD COLUMN max_clicks INT CHECK (max_clicks > 0);
+
+CREATE INDEX idx_urls_expires_at ON urls(expires_at) WHERE expires_at IS NOT NULL;
+
+COMMIT;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS stats_events_dead_letter;
+DROP TABLE IF EXISTS stats_events;
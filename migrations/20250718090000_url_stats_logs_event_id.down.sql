@@ -0,0 +1,2 @@
+-- Add down migration script here
+ALTER TABLE url_stats_logs DROP COLUMN IF EXISTS event_id;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+BEGIN;
+
+-- Queue event that produced the row, makes redelivered events idempotent
+ALTER TABLE url_stats_logs ADD COLUMN event_id BIGINT UNIQUE;
+
+COMMIT;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_url_stats_logs_accessed_at;
+DROP TABLE IF EXISTS stats_rollup_state;
+DROP TABLE IF EXISTS url_stats_daily;
+DROP TABLE IF EXISTS url_stats_hourly;
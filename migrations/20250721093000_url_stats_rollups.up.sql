@@ -0,0 +1,28 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE url_stats_hourly (
+    url_id UUID NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    clicks INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (url_id, bucket),
+    FOREIGN KEY (url_id) REFERENCES urls (id) ON DELETE CASCADE
+);
+
+CREATE TABLE url_stats_daily (
+    url_id UUID NOT NULL,
+    bucket TIMESTAMPTZ NOT NULL,
+    clicks INT NOT NULL DEFAULT 0,
+    PRIMARY KEY (url_id, bucket),
+    FOREIGN KEY (url_id) REFERENCES urls (id) ON DELETE CASCADE
+);
+
+-- Progress of the rollup job, rows are keyed by job name
+CREATE TABLE stats_rollup_state (
+    name VARCHAR(50) PRIMARY KEY,
+    rolled_up_to TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_url_stats_logs_accessed_at ON url_stats_logs(accessed_at);
+
+COMMIT;
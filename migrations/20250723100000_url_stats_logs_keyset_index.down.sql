@@ -0,0 +1,4 @@
+-- Add down migration script here
+CREATE INDEX IF NOT EXISTS idx_url_stats_logs_url_stats_id ON url_stats_logs(url_stats_id);
+
+DROP INDEX IF EXISTS idx_url_stats_logs_keyset;
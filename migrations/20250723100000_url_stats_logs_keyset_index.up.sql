@@ -0,0 +1,9 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE INDEX idx_url_stats_logs_keyset ON url_stats_logs (url_stats_id, accessed_at DESC, id DESC);
+
+-- Covered by the keyset index prefix
+DROP INDEX IF EXISTS idx_url_stats_logs_url_stats_id;
+
+COMMIT;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE url_stats_logs
+    DROP COLUMN IF EXISTS referrer_domain,
+    DROP COLUMN IF EXISTS referrer;
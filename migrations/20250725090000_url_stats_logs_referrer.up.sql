@@ -0,0 +1,8 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE url_stats_logs
+    ADD COLUMN referrer TEXT,
+    ADD COLUMN referrer_domain VARCHAR(255);
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+ALTER TABLE url_stats DROP COLUMN IF EXISTS bot_count;
+
+ALTER TABLE url_stats_logs
+    DROP COLUMN IF EXISTS is_bot,
+    DROP COLUMN IF EXISTS device,
+    DROP COLUMN IF EXISTS os,
+    DROP COLUMN IF EXISTS browser;
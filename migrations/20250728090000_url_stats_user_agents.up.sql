@@ -0,0 +1,13 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE url_stats_logs
+    ADD COLUMN browser VARCHAR(64),
+    ADD COLUMN os VARCHAR(64),
+    ADD COLUMN device VARCHAR(16),
+    ADD COLUMN is_bot BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- access_count now only counts human clicks
+ALTER TABLE url_stats ADD COLUMN bot_count INT NOT NULL DEFAULT 0;
+
+COMMIT;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE url_stats_logs
+    DROP COLUMN IF EXISTS city,
+    DROP COLUMN IF EXISTS region,
+    DROP COLUMN IF EXISTS country,
+    DROP COLUMN IF EXISTS country_code;
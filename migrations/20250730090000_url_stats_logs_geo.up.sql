@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE url_stats_logs
+    ADD COLUMN country_code VARCHAR(2),
+    ADD COLUMN country VARCHAR(128),
+    ADD COLUMN region VARCHAR(128),
+    ADD COLUMN city VARCHAR(128);
+
+COMMIT;
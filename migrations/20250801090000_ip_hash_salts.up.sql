@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+-- One random salt per UTC day, shared by every worker and deleted once the day is over
+CREATE TABLE ip_hash_salts (
+    day DATE PRIMARY KEY,
+    salt BYTEA NOT NULL
+);
+
+COMMIT;
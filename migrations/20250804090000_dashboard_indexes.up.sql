@@ -0,0 +1,8 @@
+-- Add up migration script here
+BEGIN;
+
+-- Service-wide windows scan rollups and links by time rather than by link
+CREATE INDEX IF NOT EXISTS idx_url_stats_hourly_bucket ON url_stats_hourly (bucket);
+CREATE INDEX IF NOT EXISTS idx_urls_created_at ON urls (created_at);
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Add down migration script here
+BEGIN;
+
+DELETE FROM urls WHERE deleted_at IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_urls_deleted_at;
+DROP INDEX IF EXISTS idx_urls_original_url_live;
+ALTER TABLE urls ADD CONSTRAINT urls_original_url_key UNIQUE (original_url);
+ALTER TABLE urls DROP COLUMN IF EXISTS deleted_at;
+
+COMMIT;
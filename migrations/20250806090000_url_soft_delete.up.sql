@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE urls ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- A destination only has to be unique among live links, so a trashed one can be shortened again
+ALTER TABLE urls DROP CONSTRAINT urls_original_url_key;
+CREATE UNIQUE INDEX idx_urls_original_url_live ON urls(original_url) WHERE deleted_at IS NULL;
+
+CREATE INDEX idx_urls_deleted_at ON urls(deleted_at) WHERE deleted_at IS NOT NULL;
+
+COMMIT;
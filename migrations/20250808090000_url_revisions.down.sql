@@ -0,0 +1,8 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE url_stats_logs DROP COLUMN IF EXISTS url_revision;
+DROP TABLE IF EXISTS url_revisions;
+ALTER TABLE urls DROP COLUMN IF EXISTS revision;
+
+COMMIT;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE urls ADD COLUMN revision INT NOT NULL DEFAULT 1;
+
+CREATE TABLE url_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    url_id UUID NOT NULL,
+    revision INT NOT NULL,
+    original_url TEXT NOT NULL,
+    actor_key_id UUID,
+    actor TEXT,
+    rollback_of INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (url_id, revision),
+    FOREIGN KEY (url_id) REFERENCES urls (id) ON DELETE CASCADE
+);
+
+-- History of existing links starts at their current destination, by an unknown actor
+INSERT INTO url_revisions (url_id, revision, original_url, created_at)
+SELECT id, 1, original_url, created_at FROM urls;
+
+-- Destination revision a click was redirected to, unknown for older clicks
+ALTER TABLE url_stats_logs ADD COLUMN url_revision INT;
+
+COMMIT;
@@ -0,0 +1,33 @@
+-- Add down migration script here
+BEGIN;
+
+-- Destinations are no longer limited to 225 characters, so links created since
+-- can't be kept by this revert. Refuse rather than truncate or drop them
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM urls WHERE length(original_url) > 225) THEN
+        RAISE EXCEPTION 'Cannot revert: some destinations are longer than 225 characters';
+    END IF;
+END $$;
+
+-- Several live links may now share a destination, which the unique index forbids.
+-- Which of them to keep is the operator's call, so refuse until they are cleaned up
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM urls
+        WHERE deleted_at IS NULL
+        GROUP BY original_url
+        HAVING COUNT(*) > 1
+    ) THEN
+        RAISE EXCEPTION 'Cannot revert: some destinations have several live links';
+    END IF;
+END $$;
+
+DROP INDEX IF EXISTS idx_urls_original_url;
+CREATE INDEX idx_urls_original_url ON urls(original_url);
+CREATE UNIQUE INDEX idx_urls_original_url_live ON urls(original_url) WHERE deleted_at IS NULL;
+
+ALTER TABLE urls ALTER COLUMN original_url TYPE VARCHAR(225);
+
+COMMIT;
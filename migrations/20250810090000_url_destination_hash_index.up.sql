@@ -0,0 +1,11 @@
+-- Add up migration script here
+BEGIN;
+
+-- Destinations are only limited by `ValidUrl`, and may be shared by several short codes
+ALTER TABLE urls ALTER COLUMN original_url TYPE TEXT;
+
+DROP INDEX IF EXISTS idx_urls_original_url_live;
+DROP INDEX IF EXISTS idx_urls_original_url;
+CREATE INDEX idx_urls_original_url ON urls USING HASH (original_url);
+
+COMMIT;
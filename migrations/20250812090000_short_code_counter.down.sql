@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+DROP SEQUENCE IF EXISTS short_code_counter_seq;
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+BEGIN;
+
+-- Feeds the `counter` short code strategy, each value is permuted into a distinct code
+CREATE SEQUENCE IF NOT EXISTS short_code_counter_seq AS BIGINT MINVALUE 0 START WITH 0;
+
+COMMIT;
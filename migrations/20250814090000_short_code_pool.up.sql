@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+-- Unused generated codes, claimed by creates and topped up by a background job
+CREATE TABLE IF NOT EXISTS short_code_pool (
+    short_code VARCHAR(16) PRIMARY KEY,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+COMMIT;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_url_stats_access_count;
+DROP INDEX IF EXISTS idx_urls_created_at_id;
+DROP INDEX IF EXISTS idx_urls_domain_reversed;
+ALTER TABLE urls DROP COLUMN IF EXISTS domain;
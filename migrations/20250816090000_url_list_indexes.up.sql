@@ -0,0 +1,20 @@
+-- Add up migration script here
+BEGIN;
+
+-- Destination host without a leading `www.`, matched by the link list domain filter
+ALTER TABLE urls ADD COLUMN domain TEXT GENERATED ALWAYS AS (
+    regexp_replace(
+        lower(substring(original_url FROM '^[A-Za-z][A-Za-z0-9+.-]*://(?:[^/?#@]*@)?([^/?#:]+)')),
+        '^www\.', ''
+    )
+) STORED;
+
+-- Reversed, a domain and its subdomains form one contiguous range
+CREATE INDEX idx_urls_domain_reversed ON urls ((reverse(domain) COLLATE "C"));
+CREATE INDEX idx_urls_created_at_id ON urls (created_at, id);
+
+-- Every link gets its counter row up front, so sorting by clicks walks one index
+INSERT INTO url_stats (url_id) SELECT id FROM urls ON CONFLICT (url_id) DO NOTHING;
+CREATE INDEX idx_url_stats_access_count ON url_stats (access_count, url_id);
+
+COMMIT;
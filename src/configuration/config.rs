@@ -3,12 +3,27 @@ use config::ConfigError;
 use redact::Secret;
 use serde_aux::field_attributes::deserialize_number_from_string;
 use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use std::collections::HashMap;
+use std::time::Duration;
 
 #[derive(serde::Deserialize)]
 pub struct Settings {
     pub application: ApplicationSettings,
     pub database: DatabaseSettings,
+    pub links: LinkSettings,
+    pub short_codes: ShortCodeSettings,
+    pub short_code_pool: ShortCodePoolSettings,
     pub redis: RedisSettings,
+    pub rate_limit: RateLimitSettings,
+    pub auth: AuthSettings,
+    pub stats_queue: StatsQueueSettings,
+    pub stats_rollup: StatsRollupSettings,
+    pub geoip: GeoIpSettings,
+    pub privacy: PrivacySettings,
+    pub unique_visitors: UniqueVisitorSettings,
+    pub live_stats: LiveStatsSettings,
+    pub dashboard: DashboardSettings,
+    pub trash: TrashSettings,
 }
 
 #[derive(serde::Deserialize)]
@@ -17,6 +32,10 @@ pub struct ApplicationSettings {
     #[serde(deserialize_with = "deserialize_number_from_string")]
     pub port: u16,
     pub host: String,
+    /// Comma separated addresses or CIDR ranges of the reverse proxies whose
+    /// `X-Forwarded-For` header is believed. Empty uses the connection address.
+    #[serde(default)]
+    pub trusted_proxies: String,
 }
 
 #[derive(serde::Deserialize, Clone)]
@@ -30,9 +49,303 @@ pub struct DatabaseSettings {
     pub require_ssl: bool,
 }
 
+/// When creating a link to a known destination returns the existing link instead.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupePolicy {
+    Always,
+    /// Reuse unless a custom code is requested, which always gets its own link.
+    UnlessCustomCode,
+    Never,
+}
+
+impl DedupePolicy {
+    pub fn reuses(&self, custom_code: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::UnlessCustomCode => !custom_code,
+            Self::Never => false,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct LinkSettings {
+    pub dedupe: DedupePolicy,
+}
+
+/// How short codes are generated when none is requested.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortCodeStrategy {
+    /// `length` characters drawn at random from `alphabet`.
+    Random,
+    /// A database counter permuted through a Feistel cipher and base62 encoded, never collides.
+    Counter,
+    /// Made-up words of `syllables` consonant and vowel pairs, 75 combinations each.
+    Pronounceable,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ShortCodeSettings {
+    pub strategy: ShortCodeStrategy,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub length: usize,
+    pub alphabet: String,
+    /// Leaves out characters easily mistaken for one another, such as `0/O` and `l/1`.
+    pub exclude_lookalikes: bool,
+    /// Key of the counter permutation. Changing it once codes are issued brings back collisions.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub counter_key: u64,
+    /// At least 5, giving 75^5 (about 2.4 billion) codes.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub syllables: usize,
+    /// Generated codes tried before a create fails, when they turn out to be taken.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts: usize,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct ShortCodePoolSettings {
+    /// Creates claim pre-generated codes, and only generate them on the spot when the pool runs dry.
+    pub enabled: bool,
+    /// Unused codes the refill job keeps in the pool.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub size: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub refill_batch_size: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub refill_interval_secs: u64,
+}
+
+impl ShortCodePoolSettings {
+    pub fn refill_interval(&self) -> Duration {
+        Duration::from_secs(self.refill_interval_secs)
+    }
+}
+
 #[derive(serde::Deserialize, Clone)]
 pub struct RedisSettings {
     pub uri: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub url_cache_ttl_secs: u64,
+}
+
+impl RedisSettings {
+    pub fn url_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.url_cache_ttl_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    Memory,
+    Redis,
+}
+
+#[derive(serde::Deserialize, Clone, Copy, Debug)]
+pub struct RateLimitPolicy {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub limit: u32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub window_secs: u64,
+}
+
+impl RateLimitPolicy {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct RateLimitSettings {
+    pub backend: RateLimitBackend,
+    #[serde(default)]
+    pub policies: HashMap<String, RateLimitPolicy>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct AuthSettings {
+    /// Bootstrap token granted every scope, used to mint the first API keys.
+    pub admin_token: Option<Secret<String>>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct StatsQueueSettings {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub batch_size: i64,
+    /// Clicks held in memory between redirects and the queue table, further
+    /// clicks are dropped while it is full.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub buffer_capacity: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub poll_interval_ms: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub visibility_timeout_secs: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_attempts: i32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub retry_base_delay_secs: u64,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub retry_max_delay_secs: u64,
+}
+
+/// How visitor IP addresses are stored in the click logs.
+#[derive(serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IpStorage {
+    Full,
+    /// /24 for IPv4, /48 for IPv6.
+    Truncate,
+    /// Salted hash of the full address, the salt rotating every day.
+    Hash,
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct PrivacySettings {
+    pub ip_storage: IpStorage,
+    /// Clicks sent with `DNT: 1` or `Sec-GPC: 1` are counted without any visitor data.
+    pub honor_do_not_track: bool,
+    /// Click logs and dead-lettered stats events older than this are deleted,
+    /// aggregates are kept. `0` keeps them forever.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub retention_days: u32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub purge_interval_secs: u64,
+}
+
+impl PrivacySettings {
+    pub fn retention(&self) -> Option<chrono::TimeDelta> {
+        (self.retention_days > 0).then(|| chrono::TimeDelta::days(i64::from(self.retention_days)))
+    }
+
+    pub fn purge_interval(&self) -> Duration {
+        Duration::from_secs(self.purge_interval_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct UniqueVisitorSettings {
+    /// How long hourly sketches are kept, they back hourly and non-UTC buckets.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub hourly_ttl_days: u32,
+    /// How long daily UTC sketches are kept. The all-time sketch never expires.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub daily_ttl_days: u32,
+}
+
+impl UniqueVisitorSettings {
+    pub fn hourly_ttl(&self) -> chrono::TimeDelta {
+        chrono::TimeDelta::days(i64::from(self.hourly_ttl_days))
+    }
+
+    pub fn daily_ttl(&self) -> chrono::TimeDelta {
+        chrono::TimeDelta::days(i64::from(self.daily_ttl_days))
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct LiveStatsSettings {
+    /// Clicks buffered per subscriber, a subscriber falling further behind skips ahead.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub channel_capacity: usize,
+    /// Concurrent live connections across all links.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub max_connections: usize,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub keep_alive_secs: u64,
+}
+
+impl LiveStatsSettings {
+    pub fn keep_alive(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct DashboardSettings {
+    /// How long a computed top links leaderboard is served from Redis.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub leaderboard_ttl_secs: u64,
+}
+
+impl DashboardSettings {
+    pub fn leaderboard_ttl(&self) -> Duration {
+        Duration::from_secs(self.leaderboard_ttl_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct TrashSettings {
+    /// Deleted links, with their stats, are removed for good after this. `0` keeps them forever.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub retention_days: u32,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub purge_interval_secs: u64,
+}
+
+impl TrashSettings {
+    pub fn retention(&self) -> Option<chrono::TimeDelta> {
+        (self.retention_days > 0).then(|| chrono::TimeDelta::days(i64::from(self.retention_days)))
+    }
+
+    pub fn purge_interval(&self) -> Duration {
+        Duration::from_secs(self.purge_interval_secs)
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct GeoIpSettings {
+    /// Path to a MaxMind City `.mmdb` file, geo enrichment is disabled when empty.
+    pub database_path: Option<String>,
+}
+
+impl GeoIpSettings {
+    pub fn database_path(&self) -> Option<&str> {
+        self.database_path
+            .as_deref()
+            .filter(|path| !path.trim().is_empty())
+    }
+}
+
+#[derive(serde::Deserialize, Clone)]
+pub struct StatsRollupSettings {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub interval_secs: u64,
+    /// How far back each run recomputes buckets, to absorb late-arriving clicks.
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub lateness_secs: u64,
+}
+
+impl StatsRollupSettings {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+
+    pub fn lateness(&self) -> Duration {
+        Duration::from_secs(self.lateness_secs)
+    }
+}
+
+impl StatsQueueSettings {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    pub fn visibility_timeout(&self) -> Duration {
+        Duration::from_secs(self.visibility_timeout_secs)
+    }
+
+    /// Exponential backoff, capped at the configured maximum delay.
+    pub fn retry_delay(&self, attempts: i32) -> Duration {
+        let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+        let delay = self
+            .retry_base_delay_secs
+            .saturating_mul(2u64.pow(exponent));
+
+        Duration::from_secs(delay.min(self.retry_max_delay_secs))
+    }
 }
 
 pub struct Configs;
@@ -92,3 +405,26 @@ impl DatabaseSettings {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::StatsQueueSettings;
+
+    #[test]
+    fn retry_delay_backs_off_exponentially_up_to_the_cap() {
+        let settings = StatsQueueSettings {
+            batch_size: 10,
+            buffer_capacity: 100,
+            poll_interval_ms: 500,
+            visibility_timeout_secs: 30,
+            max_attempts: 5,
+            retry_base_delay_secs: 2,
+            retry_max_delay_secs: 60,
+        };
+
+        assert_eq!(settings.retry_delay(1).as_secs(), 2);
+        assert_eq!(settings.retry_delay(2).as_secs(), 4);
+        assert_eq!(settings.retry_delay(4).as_secs(), 16);
+        assert_eq!(settings.retry_delay(10).as_secs(), 60);
+    }
+}
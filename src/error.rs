@@ -1,4 +1,7 @@
-use actix_web::{HttpResponse, ResponseError, http::StatusCode};
+use actix_web::{
+    HttpResponse, ResponseError,
+    http::{StatusCode, header},
+};
 use serde_json::{Value, json};
 use thiserror::Error;
 
@@ -12,9 +15,21 @@ pub enum AppError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Gone: {0}")]
+    Gone(String),
+
     #[error("Unauthorized: {0}")]
     Unathorized(String),
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Database error")]
     Database(#[from] sqlx::Error),
 
@@ -31,12 +46,32 @@ impl ResponseError for AppError {
             AppError::NotFound(msg) => {
                 ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::NOT_FOUND)
             }
+            AppError::Gone(msg) => {
+                ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::GONE)
+            }
+            AppError::Unathorized(msg) => {
+                let mut response =
+                    ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::UNAUTHORIZED);
+                response.headers_mut().insert(
+                    header::WWW_AUTHENTICATE,
+                    header::HeaderValue::from_static("Bearer"),
+                );
+                response
+            }
+            AppError::Forbidden(msg) => {
+                ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::FORBIDDEN)
+            }
+            AppError::Conflict(msg) => {
+                ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::CONFLICT)
+            }
+            AppError::Unavailable(msg) => {
+                ApiResponse::<Value>::fail(json!({"message": msg}), StatusCode::SERVICE_UNAVAILABLE)
+            }
             AppError::Database(err) => {
                 println!("Database error: {}", err);
                 ApiResponse::<&str>::error("A database error occurred")
             }
             AppError::Internal(_msg) => ApiResponse::<&str>::error("An internal error occurred"),
-            _ => ApiResponse::<&str>::error("An internal error occurred"),
         }
     }
 }
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+use crate::features::api_keys::{models::ApiKey, value_objects::Scope};
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyDto {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// Returned once on creation, the plaintext `key` can't be retrieved again.
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyDto {
+    pub key: String,
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+}
@@ -0,0 +1,3 @@
+mod create_api_key_dto;
+
+pub use create_api_key_dto::*;
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::features::api_keys::{errors::ApiKeyError, models::ApiKey, value_objects::Scope};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ApiKeyEntity {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyEntity {
+    pub fn to_domain(&self) -> Result<ApiKey, ApiKeyError> {
+        let scopes = self
+            .scopes
+            .iter()
+            .map(|scope| Scope::try_from(scope.as_str()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ApiKey {
+            id: self.id,
+            name: self.name.clone(),
+            prefix: self.prefix.clone(),
+            scopes,
+            created_at: self.created_at,
+            last_used_at: self.last_used_at,
+            revoked_at: self.revoked_at,
+        })
+    }
+}
@@ -0,0 +1,3 @@
+mod api_key_entity;
+
+pub use api_key_entity::*;
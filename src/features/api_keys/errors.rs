@@ -0,0 +1,43 @@
+use thiserror::Error;
+
+use crate::{error::AppError, features::api_keys::value_objects::Scope};
+
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    #[error("Missing or malformed bearer token")]
+    MissingKey,
+
+    #[error("Invalid or revoked API key")]
+    InvalidKey,
+
+    #[error("API key is missing the '{0}' scope")]
+    MissingScope(Scope),
+
+    #[error("Unknown scope: {0}")]
+    InvalidScope(String),
+
+    #[error("API key not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid API key name: {0}")]
+    InvalidName(String),
+
+    #[error("Database error")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<ApiKeyError> for AppError {
+    fn from(error: ApiKeyError) -> Self {
+        match error {
+            ApiKeyError::MissingKey | ApiKeyError::InvalidKey => {
+                AppError::Unathorized(error.to_string())
+            }
+            ApiKeyError::MissingScope(_) => AppError::Forbidden(error.to_string()),
+            ApiKeyError::InvalidScope(_) | ApiKeyError::InvalidName(_) => {
+                AppError::Validation(error.to_string())
+            }
+            ApiKeyError::NotFound(msg) => AppError::NotFound(msg),
+            ApiKeyError::Database(error) => AppError::Database(error),
+        }
+    }
+}
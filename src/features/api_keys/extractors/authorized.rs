@@ -0,0 +1,80 @@
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, http::header, web::Data};
+
+use crate::{
+    error::AppError,
+    features::api_keys::{
+        errors::ApiKeyError,
+        models::ApiKey,
+        service::IApiKeyService,
+        value_objects::{ApiKeySecret, Scope},
+    },
+    infrastructure::server::AppServices,
+};
+
+/// Compile-time marker for the scope an endpoint requires.
+pub trait RequiredScope {
+    const SCOPE: Scope;
+}
+
+pub struct LinksWrite;
+pub struct LinksDelete;
+pub struct StatsRead;
+pub struct KeysAdmin;
+
+impl RequiredScope for LinksWrite {
+    const SCOPE: Scope = Scope::LinksWrite;
+}
+
+impl RequiredScope for LinksDelete {
+    const SCOPE: Scope = Scope::LinksDelete;
+}
+
+impl RequiredScope for StatsRead {
+    const SCOPE: Scope = Scope::StatsRead;
+}
+
+impl RequiredScope for KeysAdmin {
+    const SCOPE: Scope = Scope::KeysAdmin;
+}
+
+/// Extractor validating the `Authorization: Bearer` header against the API
+/// key store. Responds with 401 for missing/unknown keys and 403 when the key
+/// lacks the `S` scope.
+pub struct Authorized<S: RequiredScope> {
+    pub api_key: ApiKey,
+    _scope: PhantomData<S>,
+}
+
+impl<S: RequiredScope> FromRequest for Authorized<S> {
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let services = req.app_data::<Data<AppServices>>().cloned();
+        let secret = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(ApiKeyError::MissingKey)
+            .and_then(ApiKeySecret::from_bearer);
+
+        Box::pin(async move {
+            let services = services.ok_or_else(|| {
+                AppError::Internal(anyhow::anyhow!("Application services are not registered"))
+            })?;
+
+            let api_key = services.api_key_service.authenticate(&secret?).await?;
+
+            if !api_key.has_scope(S::SCOPE) {
+                return Err(ApiKeyError::MissingScope(S::SCOPE).into());
+            }
+
+            Ok(Self {
+                api_key,
+                _scope: PhantomData,
+            })
+        })
+    }
+}
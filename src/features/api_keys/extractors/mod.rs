@@ -0,0 +1,3 @@
+mod authorized;
+
+pub use authorized::*;
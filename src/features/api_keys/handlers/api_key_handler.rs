@@ -0,0 +1,57 @@
+use actix_web::{
+    HttpResponse,
+    web::{Data, Json, Path},
+};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    features::api_keys::{
+        dtos::{CreateApiKeyDto, CreatedApiKeyDto},
+        extractors::{Authorized, KeysAdmin},
+        service::IApiKeyService,
+    },
+    infrastructure::server::{ApiResponse, AppServices},
+};
+
+pub struct ApiKeyHandler;
+
+impl ApiKeyHandler {
+    pub async fn create_api_key(
+        _auth: Authorized<KeysAdmin>,
+        payload: Json<CreateApiKeyDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let payload = payload.into_inner();
+
+        let (api_key, secret) = service
+            .api_key_service
+            .mint(payload.name, payload.scopes)
+            .await?;
+
+        Ok(ApiResponse::success(CreatedApiKeyDto {
+            key: secret.into_inner(),
+            api_key,
+        }))
+    }
+
+    pub async fn list_api_keys(
+        _auth: Authorized<KeysAdmin>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let api_keys = service.api_key_service.list().await?;
+
+        Ok(ApiResponse::success(api_keys))
+    }
+
+    pub async fn revoke_api_key(
+        _auth: Authorized<KeysAdmin>,
+        param: Path<Uuid>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        service.api_key_service.revoke(param.into_inner()).await?;
+
+        Ok(ApiResponse::<Value>::success_with_no_content())
+    }
+}
@@ -0,0 +1,3 @@
+mod api_key_handler;
+
+pub use api_key_handler::*;
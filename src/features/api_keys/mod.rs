@@ -0,0 +1,9 @@
+pub mod dtos;
+pub mod entities;
+pub mod errors;
+pub mod extractors;
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod service;
+pub mod value_objects;
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::features::api_keys::value_objects::Scope;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<Scope>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Key backing the bootstrap admin token from the configuration.
+    pub fn bootstrap_admin() -> Self {
+        Self {
+            id: Uuid::nil(),
+            name: String::from("bootstrap-admin"),
+            prefix: String::new(),
+            scopes: Scope::ALL.to_vec(),
+            created_at: Utc::now(),
+            last_used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.revoked_at.is_none() && self.scopes.contains(&scope)
+    }
+}
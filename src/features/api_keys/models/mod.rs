@@ -0,0 +1,3 @@
+mod api_key;
+
+pub use api_key::*;
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    features::api_keys::{
+        entities::ApiKeyEntity, errors::ApiKeyError, models::ApiKey, value_objects::Scope,
+    },
+    infrastructure::database::connection::DatabasePool,
+};
+
+/// How stale `last_used_at` may get before a request refreshes it.
+const LAST_USED_RESOLUTION_SECS: f64 = 60.0;
+
+pub trait IApiKeyRepository: Send + Sync {
+    fn save(
+        &self,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+    ) -> impl Future<Output = Result<ApiKey, ApiKeyError>> + Send;
+    fn find_active_by_hash(
+        &self,
+        key_hash: &str,
+    ) -> impl Future<Output = Result<Option<ApiKey>, ApiKeyError>> + Send;
+    fn find_all(&self) -> impl Future<Output = Result<Vec<ApiKey>, ApiKeyError>> + Send;
+    fn revoke(&self, id: Uuid) -> impl Future<Output = Result<bool, ApiKeyError>> + Send;
+}
+
+pub struct ApiKeyRepository {
+    database: Arc<DatabasePool>,
+}
+
+impl ApiKeyRepository {
+    pub fn new(database: Arc<DatabasePool>) -> Self {
+        Self { database }
+    }
+}
+
+impl IApiKeyRepository for ApiKeyRepository {
+    async fn save(
+        &self,
+        name: &str,
+        prefix: &str,
+        key_hash: &str,
+        scopes: &[Scope],
+    ) -> Result<ApiKey, ApiKeyError> {
+        let scopes: Vec<String> = scopes.iter().map(|scope| scope.to_string()).collect();
+
+        let result = sqlx::query_as!(
+            ApiKeyEntity,
+            r#"
+            INSERT INTO api_keys (name, prefix, key_hash, scopes)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#,
+            name,
+            prefix,
+            key_hash,
+            &scopes
+        )
+        .fetch_one(&self.database.pool)
+        .await?;
+
+        result.to_domain()
+    }
+
+    /// Looks up a non-revoked key and records its usage in the same round-trip.
+    /// `last_used_at` is only rewritten once it is `LAST_USED_RESOLUTION_SECS`
+    /// old, so busy keys don't update their row on every request.
+    async fn find_active_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, ApiKeyError> {
+        let result = sqlx::query_as!(
+            ApiKeyEntity,
+            r#"
+            WITH found AS (
+                SELECT * FROM api_keys
+                WHERE key_hash = $1 AND revoked_at IS NULL
+            ),
+            touched AS (
+                UPDATE api_keys SET last_used_at = NOW()
+                WHERE id IN (SELECT id FROM found)
+                  AND (last_used_at IS NULL OR last_used_at < NOW() - make_interval(secs => $2))
+            )
+            SELECT * FROM found
+            "#,
+            key_hash,
+            LAST_USED_RESOLUTION_SECS
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        result.map(|entity| entity.to_domain()).transpose()
+    }
+
+    async fn find_all(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        let result = sqlx::query_as!(
+            ApiKeyEntity,
+            "SELECT * FROM api_keys ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        result.iter().map(ApiKeyEntity::to_domain).collect()
+    }
+
+    async fn revoke(&self, id: Uuid) -> Result<bool, ApiKeyError> {
+        let result = sqlx::query!(
+            "UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(&self.database.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,103 @@
+use redact::Secret;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::features::api_keys::{
+    errors::ApiKeyError,
+    models::ApiKey,
+    repository::IApiKeyRepository,
+    value_objects::{ApiKeySecret, Scope},
+};
+
+pub trait IApiKeyService: Send + Sync {
+    fn mint(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+    ) -> impl Future<Output = Result<(ApiKey, ApiKeySecret), ApiKeyError>> + Send;
+
+    fn authenticate(
+        &self,
+        secret: &ApiKeySecret,
+    ) -> impl Future<Output = Result<ApiKey, ApiKeyError>> + Send;
+
+    fn list(&self) -> impl Future<Output = Result<Vec<ApiKey>, ApiKeyError>> + Send;
+
+    fn revoke(&self, id: Uuid) -> impl Future<Output = Result<(), ApiKeyError>> + Send;
+}
+
+#[derive(Clone)]
+pub struct ApiKeyService<R: IApiKeyRepository> {
+    repository: Arc<R>,
+    admin_token_hash: Option<String>,
+}
+
+impl<R: IApiKeyRepository> ApiKeyService<R> {
+    pub fn new(repository: Arc<R>, admin_token: Option<&Secret<String>>) -> Self {
+        let admin_token_hash = admin_token
+            .map(|token| token.expose_secret().trim())
+            .filter(|token| !token.is_empty())
+            .map(|token| ApiKeySecret::new(token).hash());
+
+        Self {
+            repository,
+            admin_token_hash,
+        }
+    }
+}
+
+impl<R: IApiKeyRepository> IApiKeyService for ApiKeyService<R> {
+    async fn mint(
+        &self,
+        name: String,
+        scopes: Vec<Scope>,
+    ) -> Result<(ApiKey, ApiKeySecret), ApiKeyError> {
+        let name = name.trim();
+
+        if name.is_empty() || name.len() > 100 {
+            return Err(ApiKeyError::InvalidName(
+                "Name must be between 1 and 100 characters".into(),
+            ));
+        }
+
+        let mut scopes = scopes;
+        scopes.sort_by_key(|scope| scope.as_str());
+        scopes.dedup();
+
+        let secret = ApiKeySecret::generate();
+        let api_key = self
+            .repository
+            .save(name, secret.prefix(), &secret.hash(), &scopes)
+            .await?;
+
+        Ok((api_key, secret))
+    }
+
+    async fn authenticate(&self, secret: &ApiKeySecret) -> Result<ApiKey, ApiKeyError> {
+        let key_hash = secret.hash();
+
+        if self.admin_token_hash.as_deref() == Some(key_hash.as_str()) {
+            return Ok(ApiKey::bootstrap_admin());
+        }
+
+        self.repository
+            .find_active_by_hash(&key_hash)
+            .await?
+            .ok_or(ApiKeyError::InvalidKey)
+    }
+
+    async fn list(&self) -> Result<Vec<ApiKey>, ApiKeyError> {
+        self.repository.find_all().await
+    }
+
+    async fn revoke(&self, id: Uuid) -> Result<(), ApiKeyError> {
+        if !self.repository.revoke(id).await? {
+            return Err(ApiKeyError::NotFound(format!(
+                "No active API key with id '{}'",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+}
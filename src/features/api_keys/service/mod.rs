@@ -0,0 +1,3 @@
+mod api_key_service;
+
+pub use api_key_service::*;
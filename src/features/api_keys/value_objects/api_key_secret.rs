@@ -0,0 +1,79 @@
+use base64::{Engine as _, engine::general_purpose};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::features::api_keys::errors::ApiKeyError;
+
+const KEY_PREFIX: &str = "sthin_";
+
+/// Plaintext API key. It is only ever shown once, when minted; the database
+/// keeps its SHA-256 hash.
+#[derive(Debug, Clone)]
+pub struct ApiKeySecret(String);
+
+impl ApiKeySecret {
+    pub fn generate() -> Self {
+        let random_bytes: [u8; 32] = rand::rng().random();
+        let encoded = general_purpose::URL_SAFE_NO_PAD.encode(random_bytes);
+
+        Self(format!("{KEY_PREFIX}{encoded}"))
+    }
+
+    pub fn new(token: impl Into<String>) -> Self {
+        Self(token.into())
+    }
+
+    /// Parses the value of an `Authorization: Bearer <key>` header.
+    pub fn from_bearer(header: &str) -> Result<Self, ApiKeyError> {
+        let token = header
+            .strip_prefix("Bearer ")
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or(ApiKeyError::MissingKey)?;
+
+        Ok(Self::new(token))
+    }
+
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.0.as_bytes()))
+    }
+
+    /// Short, non-secret identifier displayed when listing keys.
+    pub fn prefix(&self) -> &str {
+        let end = (KEY_PREFIX.len() + 6).min(self.0.len());
+        &self.0[..end]
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_keys_are_unique_and_prefixed() {
+        let first = ApiKeySecret::generate();
+        let second = ApiKeySecret::generate();
+
+        assert!(first.as_str().starts_with(KEY_PREFIX));
+        assert_ne!(first.as_str(), second.as_str());
+        assert_ne!(first.hash(), second.hash());
+        assert_eq!(first.hash().len(), 64);
+    }
+
+    #[test]
+    fn parses_bearer_header() {
+        let secret = ApiKeySecret::from_bearer("Bearer sthin_abc").unwrap();
+        assert_eq!(secret.as_str(), "sthin_abc");
+
+        assert!(ApiKeySecret::from_bearer("Basic dXNlcg==").is_err());
+        assert!(ApiKeySecret::from_bearer("Bearer   ").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+mod api_key_secret;
+mod scope;
+
+pub use api_key_secret::*;
+pub use scope::*;
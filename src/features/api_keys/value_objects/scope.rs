@@ -0,0 +1,64 @@
+use std::fmt;
+
+use crate::features::api_keys::errors::ApiKeyError;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    #[serde(rename = "links:write")]
+    LinksWrite,
+    #[serde(rename = "links:delete")]
+    LinksDelete,
+    #[serde(rename = "stats:read")]
+    StatsRead,
+    #[serde(rename = "keys:admin")]
+    KeysAdmin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [
+        Scope::LinksWrite,
+        Scope::LinksDelete,
+        Scope::StatsRead,
+        Scope::KeysAdmin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LinksWrite => "links:write",
+            Self::LinksDelete => "links:delete",
+            Self::StatsRead => "stats:read",
+            Self::KeysAdmin => "keys:admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for Scope {
+    type Error = ApiKeyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == value)
+            .ok_or_else(|| ApiKeyError::InvalidScope(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_scopes_and_rejects_unknown_ones() {
+        for scope in Scope::ALL {
+            assert_eq!(Scope::try_from(scope.as_str()).unwrap(), scope);
+        }
+
+        assert!(Scope::try_from("links:*").is_err());
+    }
+}
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct DashboardQueryDto {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Entries of a top list, 10 by default and at most 100.
+    pub limit: Option<usize>,
+}
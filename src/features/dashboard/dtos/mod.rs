@@ -0,0 +1,3 @@
+mod dashboard_query_dto;
+
+pub use dashboard_query_dto::*;
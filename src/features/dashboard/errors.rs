@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+use crate::error::AppError;
+
+#[derive(Debug, Error)]
+pub enum DashboardError {
+    #[error("Invalid dashboard query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Database error")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<DashboardError> for AppError {
+    fn from(error: DashboardError) -> Self {
+        match error {
+            DashboardError::InvalidQuery(msg) => AppError::Validation(msg),
+            DashboardError::Database(error) => AppError::Database(error),
+        }
+    }
+}
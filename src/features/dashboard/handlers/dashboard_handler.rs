@@ -0,0 +1,76 @@
+use actix_web::{
+    HttpResponse,
+    web::{Data, Query},
+};
+
+use crate::{
+    error::AppError,
+    features::{
+        api_keys::extractors::{Authorized, StatsRead},
+        dashboard::{
+            dtos::DashboardQueryDto,
+            service::{IDashboardService, LEADERBOARD_SIZE},
+            value_objects::DashboardWindow,
+        },
+    },
+    infrastructure::server::{ApiResponse, AppServices},
+};
+
+pub struct DashboardHandler;
+
+impl DashboardHandler {
+    /// Total links plus new links, active links and clicks in the window.
+    pub async fn fetch_summary(
+        _auth: Authorized<StatsRead>,
+        query: Query<DashboardQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let window = DashboardWindow::new(query.from, query.to)?;
+        let summary = service.dashboard_service.fetch_summary(window).await?;
+
+        Ok(ApiResponse::success(summary))
+    }
+
+    pub async fn fetch_top_links(
+        _auth: Authorized<StatsRead>,
+        query: Query<DashboardQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let window = DashboardWindow::new(query.from, query.to)?;
+        let links = service
+            .dashboard_service
+            .fetch_top_links(window, Self::limit(&query))
+            .await?;
+
+        Ok(ApiResponse::success(links))
+    }
+
+    pub async fn fetch_top_domains(
+        _auth: Authorized<StatsRead>,
+        query: Query<DashboardQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let window = DashboardWindow::new(query.from, query.to)?;
+        let domains = service
+            .dashboard_service
+            .fetch_top_domains(window, Self::limit(&query))
+            .await?;
+
+        Ok(ApiResponse::success(domains))
+    }
+
+    pub async fn fetch_new_links(
+        _auth: Authorized<StatsRead>,
+        query: Query<DashboardQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let window = DashboardWindow::new(query.from, query.to)?;
+        let days = service.dashboard_service.fetch_new_links(window).await?;
+
+        Ok(ApiResponse::success(days))
+    }
+
+    fn limit(query: &DashboardQueryDto) -> usize {
+        query.limit.unwrap_or(10).clamp(1, LEADERBOARD_SIZE)
+    }
+}
@@ -0,0 +1,3 @@
+mod dashboard_handler;
+
+pub use dashboard_handler::*;
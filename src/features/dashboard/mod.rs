@@ -0,0 +1,7 @@
+pub mod dtos;
+pub mod errors;
+pub mod handlers;
+pub mod models;
+pub mod repository;
+pub mod service;
+pub mod value_objects;
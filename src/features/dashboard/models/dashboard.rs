@@ -0,0 +1,45 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Service-wide totals. Clicks are human clicks from the rollups, so the
+/// last few minutes may not be counted yet.
+#[derive(Debug, Serialize)]
+pub struct DashboardSummary {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_links: i64,
+    pub new_links: i64,
+    /// Links clicked at least once in the window.
+    pub active_links: i64,
+    pub clicks: i64,
+}
+
+/// One entry of the click leaderboard, before link details are attached.
+#[derive(Debug, Clone)]
+pub struct LinkClicks {
+    pub url_id: Uuid,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopLink {
+    pub short_code: String,
+    pub original_url: String,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TopDomain {
+    pub domain: String,
+    /// Links to this domain clicked in the window.
+    pub links: i64,
+    pub access_count: i64,
+}
+
+/// Links created on a UTC day.
+#[derive(Debug, Serialize)]
+pub struct NewLinksDay {
+    pub day: NaiveDate,
+    pub links: i64,
+}
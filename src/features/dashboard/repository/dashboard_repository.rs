@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use crate::{
+    features::dashboard::{
+        errors::DashboardError,
+        models::{DashboardSummary, LinkClicks, NewLinksDay, TopDomain, TopLink},
+        value_objects::DashboardWindow,
+    },
+    infrastructure::database::connection::DatabasePool,
+};
+
+pub trait IDashboardRepository: Send + Sync {
+    fn fetch_summary(
+        &self,
+        window: &DashboardWindow,
+    ) -> impl Future<Output = Result<DashboardSummary, DashboardError>> + Send;
+
+    /// Most clicked live links in the window, most clicked first.
+    fn fetch_link_ranking(
+        &self,
+        window: &DashboardWindow,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<LinkClicks>, DashboardError>> + Send;
+
+    /// Attaches link details to a ranking, keeping its order. Links deleted
+    /// since the ranking was computed are left out.
+    fn fetch_top_links(
+        &self,
+        ranking: &[LinkClicks],
+    ) -> impl Future<Output = Result<Vec<TopLink>, DashboardError>> + Send;
+
+    /// Destination domains of live links by clicks in the window, ignoring a
+    /// leading `www.`.
+    fn fetch_top_domains(
+        &self,
+        window: &DashboardWindow,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<TopDomain>, DashboardError>> + Send;
+
+    /// Live links created per UTC day of the window, zero-filled.
+    fn fetch_new_links(
+        &self,
+        window: &DashboardWindow,
+    ) -> impl Future<Output = Result<Vec<NewLinksDay>, DashboardError>> + Send;
+}
+
+pub struct DashboardRepository {
+    database: Arc<DatabasePool>,
+}
+
+impl DashboardRepository {
+    pub fn new(database: Arc<DatabasePool>) -> Self {
+        Self { database }
+    }
+}
+
+impl IDashboardRepository for DashboardRepository {
+    async fn fetch_summary(
+        &self,
+        window: &DashboardWindow,
+    ) -> Result<DashboardSummary, DashboardError> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+              (SELECT COUNT(*) FROM urls WHERE deleted_at IS NULL) AS "total_links!",
+              (
+                SELECT COUNT(*) FROM urls
+                WHERE created_at >= $1 AND created_at < $2 AND deleted_at IS NULL
+              ) AS "new_links!",
+              COUNT(DISTINCT hourly.url_id) AS "active_links!",
+              COALESCE(SUM(hourly.clicks), 0)::bigint AS "clicks!"
+            FROM url_stats_hourly hourly
+            WHERE hourly.bucket >= $1 AND hourly.bucket < $2
+            "#,
+            window.from(),
+            window.to()
+        )
+        .fetch_one(&self.database.pool)
+        .await?;
+
+        Ok(DashboardSummary {
+            from: window.from(),
+            to: window.to(),
+            total_links: row.total_links,
+            new_links: row.new_links,
+            active_links: row.active_links,
+            clicks: row.clicks,
+        })
+    }
+
+    async fn fetch_link_ranking(
+        &self,
+        window: &DashboardWindow,
+        limit: i64,
+    ) -> Result<Vec<LinkClicks>, DashboardError> {
+        let ranking = sqlx::query_as!(
+            LinkClicks,
+            r#"
+            SELECT hourly.url_id, SUM(hourly.clicks)::bigint AS "access_count!"
+            FROM url_stats_hourly hourly
+            JOIN urls url ON url.id = hourly.url_id AND url.deleted_at IS NULL
+            WHERE hourly.bucket >= $1 AND hourly.bucket < $2
+            GROUP BY hourly.url_id
+            HAVING SUM(hourly.clicks) > 0
+            ORDER BY 2 DESC, hourly.url_id
+            LIMIT $3
+            "#,
+            window.from(),
+            window.to(),
+            limit
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(ranking)
+    }
+
+    async fn fetch_top_links(
+        &self,
+        ranking: &[LinkClicks],
+    ) -> Result<Vec<TopLink>, DashboardError> {
+        let url_ids: Vec<_> = ranking.iter().map(|entry| entry.url_id).collect();
+        let counts: Vec<_> = ranking.iter().map(|entry| entry.access_count).collect();
+
+        let links = sqlx::query_as!(
+            TopLink,
+            r#"
+            SELECT url.short_code, url.original_url, ranking.access_count AS "access_count!"
+            FROM UNNEST($1::uuid[], $2::bigint[]) WITH ORDINALITY
+                AS ranking(url_id, access_count, position)
+            JOIN urls url ON url.id = ranking.url_id AND url.deleted_at IS NULL
+            ORDER BY ranking.position
+            "#,
+            &url_ids,
+            &counts
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(links)
+    }
+
+    async fn fetch_top_domains(
+        &self,
+        window: &DashboardWindow,
+        limit: i64,
+    ) -> Result<Vec<TopDomain>, DashboardError> {
+        let domains = sqlx::query_as!(
+            TopDomain,
+            r#"
+            WITH clicks AS (
+                SELECT hourly.url_id, SUM(hourly.clicks) AS clicks
+                FROM url_stats_hourly hourly
+                WHERE hourly.bucket >= $1 AND hourly.bucket < $2
+                GROUP BY hourly.url_id
+            ),
+            domains AS (
+                SELECT url.domain, clicks.clicks
+                FROM clicks
+                JOIN urls url ON url.id = clicks.url_id AND url.deleted_at IS NULL
+            )
+            SELECT domain AS "domain!", COUNT(*) AS "links!", SUM(clicks)::bigint AS "access_count!"
+            FROM domains
+            WHERE domain IS NOT NULL
+            GROUP BY domain
+            HAVING SUM(clicks) > 0
+            ORDER BY 3 DESC, domain
+            LIMIT $3
+            "#,
+            window.from(),
+            window.to(),
+            limit
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(domains)
+    }
+
+    async fn fetch_new_links(
+        &self,
+        window: &DashboardWindow,
+    ) -> Result<Vec<NewLinksDay>, DashboardError> {
+        let days = sqlx::query_as!(
+            NewLinksDay,
+            r#"
+            WITH days AS (
+                SELECT generate_series(
+                    date_trunc('day', $1::timestamptz AT TIME ZONE 'UTC'),
+                    date_trunc('day', ($2::timestamptz - interval '1 microsecond') AT TIME ZONE 'UTC'),
+                    interval '1 day'
+                ) AS day
+            )
+            SELECT days.day::date AS "day!", COUNT(url.id) AS "links!"
+            FROM days
+            LEFT JOIN urls url
+                ON url.created_at >= GREATEST(days.day AT TIME ZONE 'UTC', $1)
+               AND url.created_at < LEAST((days.day + interval '1 day') AT TIME ZONE 'UTC', $2)
+               AND url.deleted_at IS NULL
+            GROUP BY days.day
+            ORDER BY days.day
+            "#,
+            window.from(),
+            window.to()
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(days)
+    }
+}
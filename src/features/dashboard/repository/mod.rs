@@ -0,0 +1,3 @@
+mod dashboard_repository;
+
+pub use dashboard_repository::*;
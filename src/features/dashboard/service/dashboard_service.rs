@@ -0,0 +1,153 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    features::dashboard::{
+        errors::DashboardError,
+        models::{DashboardSummary, LinkClicks, NewLinksDay, TopDomain, TopLink},
+        repository::IDashboardRepository,
+        value_objects::DashboardWindow,
+    },
+    infrastructure::cache::redis_cache::RedisCache,
+};
+
+/// Entries kept per cached leaderboard, and the largest `limit` served.
+pub const LEADERBOARD_SIZE: usize = 100;
+
+pub trait IDashboardService: Send + Sync {
+    fn fetch_summary(
+        &self,
+        window: DashboardWindow,
+    ) -> impl Future<Output = Result<DashboardSummary, DashboardError>> + Send;
+
+    fn fetch_top_links(
+        &self,
+        window: DashboardWindow,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<TopLink>, DashboardError>> + Send;
+
+    fn fetch_top_domains(
+        &self,
+        window: DashboardWindow,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<TopDomain>, DashboardError>> + Send;
+
+    fn fetch_new_links(
+        &self,
+        window: DashboardWindow,
+    ) -> impl Future<Output = Result<Vec<NewLinksDay>, DashboardError>> + Send;
+}
+
+#[derive(Clone)]
+pub struct DashboardService<R: IDashboardRepository> {
+    repository: Arc<R>,
+    cache: Arc<RedisCache>,
+    leaderboard_ttl: Duration,
+}
+
+impl<R: IDashboardRepository> DashboardService<R> {
+    pub fn new(repository: Arc<R>, cache: Arc<RedisCache>, leaderboard_ttl: Duration) -> Self {
+        Self {
+            repository,
+            cache,
+            leaderboard_ttl,
+        }
+    }
+
+    fn leaderboard_key(window: &DashboardWindow) -> String {
+        format!(
+            "leaderboard:{}:{}",
+            window.from().timestamp(),
+            window.to().timestamp()
+        )
+    }
+
+    /// Reads the ranking from its sorted set, computing and caching the full
+    /// leaderboard on a miss. Cache failures fall through to the database.
+    async fn link_ranking(
+        &self,
+        window: &DashboardWindow,
+        limit: usize,
+    ) -> Result<Vec<LinkClicks>, DashboardError> {
+        let key = Self::leaderboard_key(window);
+
+        match self.cache.sorted_set_top(&key, limit).await {
+            Ok(cached) if !cached.is_empty() => {
+                return Ok(cached
+                    .into_iter()
+                    .filter_map(|(member, access_count)| {
+                        Some(LinkClicks {
+                            url_id: member.parse().ok()?,
+                            access_count,
+                        })
+                    })
+                    .collect());
+            }
+            Ok(_) => {}
+            Err(error) => tracing::warn!("Failed to read cached leaderboard: {}", error),
+        }
+
+        let mut ranking = self
+            .repository
+            .fetch_link_ranking(window, LEADERBOARD_SIZE as i64)
+            .await?;
+
+        let members: Vec<(String, i64)> = ranking
+            .iter()
+            .map(|entry| (entry.url_id.to_string(), entry.access_count))
+            .collect();
+
+        if let Err(error) = self
+            .cache
+            .replace_sorted_set(&key, &members, self.leaderboard_ttl)
+            .await
+        {
+            tracing::warn!("Failed to cache leaderboard: {}", error);
+        }
+
+        ranking.truncate(limit);
+
+        Ok(ranking)
+    }
+}
+
+impl<R: IDashboardRepository> IDashboardService for DashboardService<R> {
+    async fn fetch_summary(
+        &self,
+        window: DashboardWindow,
+    ) -> Result<DashboardSummary, DashboardError> {
+        self.repository.fetch_summary(&window).await
+    }
+
+    async fn fetch_top_links(
+        &self,
+        window: DashboardWindow,
+        limit: usize,
+    ) -> Result<Vec<TopLink>, DashboardError> {
+        let ranking = self
+            .link_ranking(&window, limit.min(LEADERBOARD_SIZE))
+            .await?;
+
+        if ranking.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.repository.fetch_top_links(&ranking).await
+    }
+
+    async fn fetch_top_domains(
+        &self,
+        window: DashboardWindow,
+        limit: usize,
+    ) -> Result<Vec<TopDomain>, DashboardError> {
+        self.repository
+            .fetch_top_domains(&window, limit.min(LEADERBOARD_SIZE) as i64)
+            .await
+    }
+
+    async fn fetch_new_links(
+        &self,
+        window: DashboardWindow,
+    ) -> Result<Vec<NewLinksDay>, DashboardError> {
+        self.repository.fetch_new_links(&window).await
+    }
+}
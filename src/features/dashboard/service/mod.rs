@@ -0,0 +1,3 @@
+mod dashboard_service;
+
+pub use dashboard_service::*;
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+
+use crate::features::dashboard::errors::DashboardError;
+
+const DEFAULT_DAYS: i64 = 7;
+const MAX_DAYS: i64 = 366;
+
+/// Service-wide `[from, to)` reporting window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DashboardWindow {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+impl DashboardWindow {
+    /// Defaults to the last 7 days. Bounds are widened to whole hours, the
+    /// granularity of the click rollups the dashboard reads from.
+    pub fn new(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Self, DashboardError> {
+        let to = to.unwrap_or_else(Utc::now);
+        let from = from.unwrap_or(to - Duration::days(DEFAULT_DAYS));
+
+        if from >= to {
+            return Err(DashboardError::InvalidQuery(
+                "`from` must be earlier than `to`".into(),
+            ));
+        }
+
+        if to - from > Duration::days(MAX_DAYS) {
+            return Err(DashboardError::InvalidQuery(format!(
+                "Window cannot exceed {MAX_DAYS} days"
+            )));
+        }
+
+        let hour = Duration::hours(1);
+        let from = from.duration_trunc(hour).unwrap_or(from);
+        let floor = to.duration_trunc(hour).unwrap_or(to);
+        let to = if floor < to { floor + hour } else { floor };
+
+        Ok(Self { from, to })
+    }
+
+    pub fn from(&self) -> DateTime<Utc> {
+        self.from
+    }
+
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn widens_bounds_to_whole_hours() {
+        let from = Utc.with_ymd_and_hms(2025, 8, 1, 10, 15, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2025, 8, 2, 9, 0, 1).unwrap();
+
+        let window = DashboardWindow::new(Some(from), Some(to)).unwrap();
+
+        assert_eq!(
+            window.from(),
+            Utc.with_ymd_and_hms(2025, 8, 1, 10, 0, 0).unwrap()
+        );
+        assert_eq!(
+            window.to(),
+            Utc.with_ymd_and_hms(2025, 8, 2, 10, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn defaults_to_a_week_and_rejects_bad_windows() {
+        let window = DashboardWindow::new(None, None).unwrap();
+        let now = Utc::now();
+
+        assert!((window.to() - window.from()).num_days() >= DEFAULT_DAYS);
+        assert!(DashboardWindow::new(Some(now), Some(now - Duration::hours(1))).is_err());
+        assert!(DashboardWindow::new(Some(now - Duration::days(400)), Some(now)).is_err());
+    }
+}
@@ -0,0 +1,3 @@
+mod dashboard_window;
+
+pub use dashboard_window::*;
@@ -1,2 +1,4 @@
+pub mod api_keys;
+pub mod dashboard;
 pub mod url_stats;
 pub mod urls;
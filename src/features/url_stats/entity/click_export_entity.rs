@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::features::url_stats::model::ClickExportRow;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ClickExportEntity {
+    pub id: Uuid,
+    pub short_code: String,
+    pub accessed_at: DateTime<Utc>,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub referrer: Option<String>,
+    pub referrer_domain: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub url_revision: Option<i32>,
+}
+
+impl ClickExportEntity {
+    pub fn to_domain(self) -> ClickExportRow {
+        ClickExportRow {
+            id: self.id,
+            short_code: self.short_code,
+            accessed_at: self.accessed_at,
+            ip_address: self.ip_address,
+            user_agent: self.user_agent,
+            referrer: self.referrer,
+            referrer_domain: self.referrer_domain,
+            browser: self.browser,
+            os: self.os,
+            device: self.device,
+            is_bot: self.is_bot,
+            country_code: self.country_code,
+            country: self.country,
+            region: self.region,
+            city: self.city,
+            url_revision: self.url_revision,
+        }
+    }
+}
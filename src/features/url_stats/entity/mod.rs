@@ -1,7 +1,11 @@
+mod click_export_entity;
 mod url_stats_entity;
 mod url_stats_log_entity;
 mod url_stats_report_entity;
+mod url_stats_timeseries_entity;
 
+pub use click_export_entity::*;
 pub use url_stats_entity::*;
 pub use url_stats_log_entity::*;
 pub use url_stats_report_entity::*;
+pub use url_stats_timeseries_entity::*;
@@ -9,6 +9,7 @@ pub struct UrlStatsEntity {
     pub id: Uuid,
     pub url_id: Uuid,
     pub access_count: i32,
+    pub bot_count: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -20,6 +21,7 @@ impl UrlStatsEntity {
             id: stats.id.unwrap_or_else(Uuid::new_v4),
             url_id: stats.url_id,
             access_count: stats.access_count,
+            bot_count: stats.bot_count,
             created_at: stats.created_at.unwrap_or_else(Utc::now),
             updated_at: stats.updated_at.unwrap_or_else(Utc::now),
             deleted_at: stats.deleted_at,
@@ -31,6 +33,7 @@ impl UrlStatsEntity {
             id: Some(self.id),
             url_id: self.url_id,
             access_count: self.access_count,
+            bot_count: self.bot_count,
             created_at: Some(self.created_at),
             updated_at: Some(self.updated_at),
             deleted_at: self.deleted_at,
@@ -10,7 +10,18 @@ pub struct UrlStatsLog {
     pub url_stats_id: Uuid,
     pub ip_address: String,
     pub user_agent: String,
+    pub referrer: Option<String>,
+    pub referrer_domain: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub accessed_at: DateTime<Utc>,
+    pub url_revision: Option<i32>,
 }
 
 impl UrlStatsLog {
@@ -20,7 +31,18 @@ impl UrlStatsLog {
             url_stats_id: model.url_stats_id,
             ip_address: model.ip_address,
             user_agent: model.user_agent,
+            referrer: model.referrer,
+            referrer_domain: model.referrer_domain,
+            browser: model.browser,
+            os: model.os,
+            device: model.device,
+            is_bot: model.is_bot,
+            country_code: model.country_code,
+            country: model.country,
+            region: model.region,
+            city: model.city,
             accessed_at: model.accessed_at.unwrap_or_else(Utc::now),
+            url_revision: model.url_revision,
         }
     }
 
@@ -30,7 +52,18 @@ impl UrlStatsLog {
             url_stats_id: self.url_stats_id,
             ip_address: self.ip_address,
             user_agent: self.user_agent,
+            referrer: self.referrer,
+            referrer_domain: self.referrer_domain,
+            browser: self.browser,
+            os: self.os,
+            device: self.device,
+            is_bot: self.is_bot,
+            country_code: self.country_code,
+            country: self.country,
+            region: self.region,
+            city: self.city,
             accessed_at: Some(self.accessed_at),
+            url_revision: self.url_revision,
         }
     }
 }
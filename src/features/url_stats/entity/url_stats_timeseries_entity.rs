@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::prelude::FromRow;
+
+use crate::features::url_stats::model::TimeseriesBucket;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TimeseriesBucketEntity {
+    pub bucket: DateTime<Utc>,
+    pub access_count: i64,
+}
+
+impl TimeseriesBucketEntity {
+    pub fn to_domain(&self, tz: Tz) -> TimeseriesBucket {
+        TimeseriesBucket {
+            bucket: self.bucket.with_timezone(&tz),
+            access_count: self.access_count,
+            unique_visitors: None,
+        }
+    }
+}
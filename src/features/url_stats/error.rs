@@ -1,13 +1,35 @@
 use thiserror::Error;
 
+use crate::error::AppError;
+
 #[derive(Debug, Error)]
 pub enum UrlStatsError {
     #[error("Missing URL ID in event")]
     MissingUrlId,
 
+    #[error("Invalid stats query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Failed to (de)serialize stats event: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("Failed to encode export: {0}")]
+    Export(String),
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
     #[error("Internal system error")]
     Unexpected(#[from] anyhow::Error),
 }
+
+impl From<UrlStatsError> for AppError {
+    fn from(error: UrlStatsError) -> Self {
+        match error {
+            UrlStatsError::InvalidQuery(msg) => AppError::Validation(msg),
+            UrlStatsError::Database(error) => AppError::Database(error),
+            UrlStatsError::Unexpected(error) => AppError::Internal(error),
+            error => AppError::Internal(error.into()),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Int32Array, RecordBatch, StringArray, TimestampMicrosecondArray,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use async_stream::try_stream;
+use futures_util::{TryStreamExt, stream::BoxStream};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+
+use crate::features::url_stats::{
+    error::UrlStatsError, model::ClickExportRow, value_objects::ExportFormat,
+};
+
+/// Encoded export output, chunk by chunk.
+pub type ExportStream = BoxStream<'static, Result<Vec<u8>, UrlStatsError>>;
+
+/// Text formats hand a chunk over once this much output is buffered.
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Parquet output is handed over one row group at a time.
+const ROW_GROUP_ROWS: usize = 8192;
+
+const COLUMNS: [&str; 16] = [
+    "id",
+    "short_code",
+    "accessed_at",
+    "ip_address",
+    "user_agent",
+    "referrer",
+    "referrer_domain",
+    "browser",
+    "os",
+    "device",
+    "is_bot",
+    "country_code",
+    "country",
+    "region",
+    "city",
+    "url_revision",
+];
+
+/// Incrementally encodes click rows, so an export is streamed in bounded
+/// chunks instead of being built in memory.
+pub enum ExportEncoder {
+    Csv(Box<csv::Writer<Vec<u8>>>),
+    Ndjson(Vec<u8>),
+    Parquet {
+        writer: Box<ArrowWriter<Vec<u8>>>,
+        rows: Vec<ClickExportRow>,
+    },
+}
+
+impl ExportEncoder {
+    pub fn new(format: ExportFormat) -> Result<Self, UrlStatsError> {
+        Ok(match format {
+            ExportFormat::Csv => {
+                let mut writer = Self::csv_writer();
+                writer.write_record(COLUMNS).map_err(Self::error)?;
+                Self::Csv(Box::new(writer))
+            }
+            ExportFormat::Ndjson => Self::Ndjson(Vec::with_capacity(CHUNK_BYTES)),
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .set_max_row_group_row_count(Some(ROW_GROUP_ROWS))
+                    .build();
+                let writer = ArrowWriter::try_new(Vec::new(), Self::schema(), Some(properties))
+                    .map_err(Self::error)?;
+
+                Self::Parquet {
+                    writer: Box::new(writer),
+                    rows: Vec::with_capacity(ROW_GROUP_ROWS),
+                }
+            }
+        })
+    }
+
+    /// Encodes a row, returning a chunk whenever enough output is ready.
+    pub fn write(&mut self, row: ClickExportRow) -> Result<Option<Vec<u8>>, UrlStatsError> {
+        match self {
+            Self::Csv(writer) => {
+                writer.serialize(&row).map_err(Self::error)?;
+
+                if writer.get_ref().len() < CHUNK_BYTES {
+                    return Ok(None);
+                }
+
+                let writer = std::mem::replace(writer.as_mut(), Self::csv_writer());
+                writer.into_inner().map(Some).map_err(Self::error)
+            }
+            Self::Ndjson(buffer) => {
+                serde_json::to_writer(&mut *buffer, &row)?;
+                buffer.push(b'\n');
+
+                if buffer.len() < CHUNK_BYTES {
+                    return Ok(None);
+                }
+
+                Ok(Some(std::mem::replace(
+                    buffer,
+                    Vec::with_capacity(CHUNK_BYTES),
+                )))
+            }
+            Self::Parquet { writer, rows } => {
+                rows.push(row);
+
+                if rows.len() < ROW_GROUP_ROWS {
+                    return Ok(None);
+                }
+
+                writer
+                    .write(&Self::record_batch(rows)?)
+                    .map_err(Self::error)?;
+                rows.clear();
+                writer.flush().map_err(Self::error)?;
+                writer.sync().map_err(Self::error)?;
+
+                // Only drained after a sync, so the writer's byte offsets stay valid
+                Ok(Some(std::mem::take(writer.inner_mut())))
+            }
+        }
+    }
+
+    /// Returns whatever output is left, including the Parquet footer.
+    pub fn finish(self) -> Result<Vec<u8>, UrlStatsError> {
+        match self {
+            Self::Csv(writer) => writer.into_inner().map_err(Self::error),
+            Self::Ndjson(buffer) => Ok(buffer),
+            Self::Parquet { mut writer, rows } => {
+                if !rows.is_empty() {
+                    writer
+                        .write(&Self::record_batch(&rows)?)
+                        .map_err(Self::error)?;
+                }
+
+                writer.finish().map_err(Self::error)?;
+
+                Ok(std::mem::take(writer.inner_mut()))
+            }
+        }
+    }
+
+    /// Encodes `rows` as they arrive, never holding more than a chunk or a
+    /// row group in memory.
+    pub fn encode(
+        format: ExportFormat,
+        mut rows: BoxStream<'static, Result<ClickExportRow, UrlStatsError>>,
+    ) -> ExportStream {
+        Box::pin(try_stream! {
+            let mut encoder = Self::new(format)?;
+
+            while let Some(row) = rows.try_next().await? {
+                if let Some(chunk) = encoder.write(row)? {
+                    yield chunk;
+                }
+            }
+
+            yield encoder.finish()?;
+        })
+    }
+
+    fn csv_writer() -> csv::Writer<Vec<u8>> {
+        csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(Vec::with_capacity(CHUNK_BYTES))
+    }
+
+    fn schema() -> SchemaRef {
+        let text = |name: &str, nullable: bool| Field::new(name, DataType::Utf8, nullable);
+
+        Arc::new(Schema::new(vec![
+            text(COLUMNS[0], false),
+            text(COLUMNS[1], false),
+            Field::new(
+                COLUMNS[2],
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                false,
+            ),
+            text(COLUMNS[3], false),
+            text(COLUMNS[4], false),
+            text(COLUMNS[5], true),
+            text(COLUMNS[6], true),
+            text(COLUMNS[7], true),
+            text(COLUMNS[8], true),
+            text(COLUMNS[9], true),
+            Field::new(COLUMNS[10], DataType::Boolean, false),
+            text(COLUMNS[11], true),
+            text(COLUMNS[12], true),
+            text(COLUMNS[13], true),
+            text(COLUMNS[14], true),
+            Field::new(COLUMNS[15], DataType::Int32, true),
+        ]))
+    }
+
+    fn record_batch(rows: &[ClickExportRow]) -> Result<RecordBatch, UrlStatsError> {
+        let text = |value: fn(&ClickExportRow) -> Option<&str>| -> ArrayRef {
+            Arc::new(rows.iter().map(value).collect::<StringArray>())
+        };
+
+        let ids: ArrayRef = Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|row| row.id.to_string()),
+        ));
+        let accessed_at: ArrayRef = Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                rows.iter().map(|row| row.accessed_at.timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        );
+        let bots: ArrayRef = Arc::new(
+            rows.iter()
+                .map(|row| Some(row.is_bot))
+                .collect::<BooleanArray>(),
+        );
+        let revisions: ArrayRef = Arc::new(
+            rows.iter()
+                .map(|row| row.url_revision)
+                .collect::<Int32Array>(),
+        );
+
+        RecordBatch::try_new(
+            Self::schema(),
+            vec![
+                ids,
+                text(|row| Some(&row.short_code)),
+                accessed_at,
+                text(|row| Some(&row.ip_address)),
+                text(|row| Some(&row.user_agent)),
+                text(|row| row.referrer.as_deref()),
+                text(|row| row.referrer_domain.as_deref()),
+                text(|row| row.browser.as_deref()),
+                text(|row| row.os.as_deref()),
+                text(|row| row.device.as_deref()),
+                bots,
+                text(|row| row.country_code.as_deref()),
+                text(|row| row.country.as_deref()),
+                text(|row| row.region.as_deref()),
+                text(|row| row.city.as_deref()),
+                revisions,
+            ],
+        )
+        .map_err(Self::error)
+    }
+
+    fn error(error: impl std::fmt::Display) -> UrlStatsError {
+        UrlStatsError::Export(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::web::Bytes;
+    use arrow_array::Array;
+    use chrono::{TimeZone, Utc};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn row(short_code: &str) -> ClickExportRow {
+        ClickExportRow {
+            id: Uuid::nil(),
+            short_code: short_code.to_string(),
+            accessed_at: Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 0).unwrap(),
+            ip_address: String::from("203.0.113.0"),
+            user_agent: String::from("Mozilla/5.0"),
+            referrer: Some(String::from("https://news.ycombinator.com/")),
+            referrer_domain: Some(String::from("news.ycombinator.com")),
+            browser: Some(String::from("Chrome")),
+            os: None,
+            device: Some(String::from("desktop")),
+            is_bot: false,
+            country_code: Some(String::from("NL")),
+            country: None,
+            region: None,
+            city: None,
+            url_revision: Some(2),
+        }
+    }
+
+    fn encode(format: ExportFormat, rows: Vec<ClickExportRow>) -> Vec<u8> {
+        let mut encoder = ExportEncoder::new(format).unwrap();
+        let mut output = Vec::new();
+
+        for row in rows {
+            if let Some(chunk) = encoder.write(row).unwrap() {
+                output.extend(chunk);
+            }
+        }
+
+        output.extend(encoder.finish().unwrap());
+        output
+    }
+
+    #[test]
+    fn csv_starts_with_the_header_in_column_order() {
+        let output = String::from_utf8(encode(ExportFormat::Csv, vec![row("abc123")])).unwrap();
+        let mut lines = output.lines();
+
+        assert_eq!(lines.next(), Some(COLUMNS.join(",").as_str()));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "00000000-0000-0000-0000-000000000000,abc123,2026-03-01T12:30:00Z,203.0.113.0,\
+                 Mozilla/5.0,https://news.ycombinator.com/,news.ycombinator.com,Chrome,,desktop,\
+                 false,NL,,,,2"
+            )
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn ndjson_writes_one_object_per_line() {
+        let output = encode(ExportFormat::Ndjson, vec![row("abc123"), row("def456")]);
+        let lines: Vec<serde_json::Value> = output
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["short_code"], "abc123");
+        assert_eq!(lines[1]["short_code"], "def456");
+        assert_eq!(lines[0]["os"], serde_json::Value::Null);
+        assert_eq!(lines[0]["url_revision"], 2);
+    }
+
+    #[test]
+    fn parquet_round_trips_across_row_groups() {
+        let rows = (0..ROW_GROUP_ROWS + 5)
+            .map(|index| row(&format!("code{index}")))
+            .collect();
+        let output = encode(ExportFormat::Parquet, rows);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(Bytes::from(output))
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+
+        assert_eq!(batches[0].schema(), ExportEncoder::schema());
+        assert_eq!(
+            batches.iter().map(RecordBatch::num_rows).sum::<usize>(),
+            ROW_GROUP_ROWS + 5
+        );
+
+        let last = batches.last().unwrap();
+        let short_codes = last
+            .column_by_name("short_code")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let os = last.column_by_name("os").unwrap();
+
+        assert_eq!(
+            short_codes.value(short_codes.len() - 1),
+            format!("code{}", ROW_GROUP_ROWS + 4)
+        );
+        assert!(os.is_null(0));
+    }
+}
@@ -0,0 +1,3 @@
+mod export_encoder;
+
+pub use export_encoder::*;
@@ -0,0 +1,5 @@
+mod stats_retention_job;
+mod stats_rollup_job;
+
+pub use stats_retention_job::*;
+pub use stats_rollup_job::*;
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+
+use crate::{
+    configuration::PrivacySettings,
+    features::url_stats::{
+        queue::{IStatsQueue, PgStatsQueue},
+        repository::UrlStatsRepository,
+        service::{IUrlStatsService, UrlStatsService},
+    },
+};
+
+/// Periodically deletes click logs and dead-lettered stats events past the
+/// retention period, and expired IP hash salts.
+pub struct StatsRetentionJob {
+    service: Arc<UrlStatsService<UrlStatsRepository>>,
+    queue: Arc<PgStatsQueue>,
+    settings: PrivacySettings,
+}
+
+impl StatsRetentionJob {
+    pub fn spawn(
+        service: Arc<UrlStatsService<UrlStatsRepository>>,
+        queue: Arc<PgStatsQueue>,
+        settings: PrivacySettings,
+    ) {
+        let job = Self {
+            service,
+            queue,
+            settings,
+        };
+
+        tokio::spawn(job.run());
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(self.settings.purge_interval());
+
+        loop {
+            ticker.tick().await;
+
+            match self.service.purge_expired().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired click logs", purged),
+                Err(error) => tracing::error!("Failed to purge expired click logs: {}", error),
+            }
+
+            let Some(retention) = self.settings.retention() else {
+                continue;
+            };
+
+            match self
+                .queue
+                .purge_dead_letters_before(Utc::now() - retention)
+                .await
+            {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} dead-lettered stats events", purged),
+                Err(error) => {
+                    tracing::error!("Failed to purge dead-lettered stats events: {}", error)
+                }
+            }
+        }
+    }
+}
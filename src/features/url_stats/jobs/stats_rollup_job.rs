@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use crate::{
+    configuration::StatsRollupSettings,
+    features::url_stats::{
+        repository::UrlStatsRepository,
+        service::{IUrlStatsService, UrlStatsService},
+    },
+};
+
+/// Periodically folds raw click logs into the hourly and daily rollup tables.
+pub struct StatsRollupJob {
+    service: Arc<UrlStatsService<UrlStatsRepository>>,
+    settings: StatsRollupSettings,
+}
+
+impl StatsRollupJob {
+    pub fn spawn(service: Arc<UrlStatsService<UrlStatsRepository>>, settings: StatsRollupSettings) {
+        let job = Self { service, settings };
+
+        tokio::spawn(job.run());
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(self.settings.interval());
+
+        loop {
+            ticker.tick().await;
+
+            if let Err(error) = self.service.refresh_rollups(self.settings.lateness()).await {
+                tracing::error!("Failed to refresh stats rollups: {}", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+use crate::{configuration::LiveStatsSettings, features::url_stats::model::LiveClick};
+
+type Channels = Arc<Mutex<HashMap<Uuid, broadcast::Sender<Arc<LiveClick>>>>>;
+
+/// Fans recorded clicks out to live subscribers, through one channel per
+/// watched link so subscribers only ever receive clicks on their own link.
+///
+/// Only clicks recorded by this instance's stats worker are published, so
+/// with several instances a subscriber sees the share of clicks its instance
+/// happened to process.
+///
+/// Channels are bounded: the stats worker never waits on a slow consumer,
+/// which instead loses the oldest clicks and is told how many it skipped.
+pub struct LiveClickHub {
+    channels: Channels,
+    channel_capacity: usize,
+    connections: Arc<AtomicUsize>,
+    max_connections: usize,
+    keep_alive: Duration,
+}
+
+pub enum LiveEvent {
+    Click(Arc<LiveClick>),
+    /// The subscriber fell behind and this many clicks were dropped.
+    Lagged(u64),
+}
+
+impl LiveClickHub {
+    pub fn new(settings: &LiveStatsSettings) -> Self {
+        Self {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+            channel_capacity: settings.channel_capacity.max(1),
+            connections: Arc::new(AtomicUsize::new(0)),
+            max_connections: settings.max_connections,
+            keep_alive: settings.keep_alive(),
+        }
+    }
+
+    /// How often idle connections get a comment, so proxies keep them open.
+    pub fn keep_alive(&self) -> Duration {
+        self.keep_alive
+    }
+
+    pub fn publish(&self, clicks: Vec<LiveClick>) {
+        let channels = self.channels.lock().unwrap();
+
+        if channels.is_empty() {
+            return;
+        }
+
+        for click in clicks {
+            if let Some(sender) = channels.get(&click.url_id) {
+                // Only fails when the last subscriber just left
+                let _ = sender.send(Arc::new(click));
+            }
+        }
+    }
+
+    /// Returns `None` once `max_connections` subscriptions are open.
+    pub fn subscribe(&self, url_id: Uuid) -> Option<LiveSubscription> {
+        self.connections
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |open| {
+                (open < self.max_connections).then_some(open + 1)
+            })
+            .ok()?;
+
+        let receiver = self
+            .channels
+            .lock()
+            .unwrap()
+            .entry(url_id)
+            .or_insert_with(|| broadcast::channel(self.channel_capacity).0)
+            .subscribe();
+
+        Some(LiveSubscription {
+            url_id,
+            receiver,
+            channels: self.channels.clone(),
+            connections: self.connections.clone(),
+        })
+    }
+}
+
+/// Live clicks of a single link. The connection slot is released on drop,
+/// along with the link's channel when this was its last subscriber.
+pub struct LiveSubscription {
+    url_id: Uuid,
+    receiver: broadcast::Receiver<Arc<LiveClick>>,
+    channels: Channels,
+    connections: Arc<AtomicUsize>,
+}
+
+impl LiveSubscription {
+    /// Waits for the next click on the subscribed link, `None` once the hub is gone.
+    pub async fn next(&mut self) -> Option<LiveEvent> {
+        match self.receiver.recv().await {
+            Ok(click) => Some(LiveEvent::Click(click)),
+            Err(RecvError::Lagged(skipped)) => Some(LiveEvent::Lagged(skipped)),
+            Err(RecvError::Closed) => None,
+        }
+    }
+}
+
+impl Drop for LiveSubscription {
+    fn drop(&mut self) {
+        let mut channels = self.channels.lock().unwrap();
+
+        // Our own receiver is still counted until the fields are dropped
+        if channels
+            .get(&self.url_id)
+            .is_some_and(|sender| sender.receiver_count() <= 1)
+        {
+            channels.remove(&self.url_id);
+        }
+
+        self.connections.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    use super::*;
+    use crate::features::url_stats::value_objects::{DeviceType, ReferrerChannel};
+
+    fn hub(max_connections: usize) -> LiveClickHub {
+        LiveClickHub::new(&LiveStatsSettings {
+            channel_capacity: 4,
+            max_connections,
+            keep_alive_secs: 15,
+        })
+    }
+
+    fn click(url_id: Uuid) -> LiveClick {
+        LiveClick {
+            url_id,
+            accessed_at: Utc::now(),
+            country_code: None,
+            country: None,
+            device: DeviceType::Desktop,
+            browser: String::from("Chrome"),
+            is_bot: false,
+            referrer: None,
+            channel: ReferrerChannel::Direct,
+        }
+    }
+
+    #[test]
+    fn caps_connections_and_releases_slots_on_drop() {
+        let hub = hub(2);
+        let first = hub.subscribe(Uuid::new_v4()).unwrap();
+        let second = hub.subscribe(Uuid::new_v4()).unwrap();
+
+        assert!(hub.subscribe(Uuid::new_v4()).is_none());
+
+        drop(first);
+        let third = hub.subscribe(Uuid::new_v4()).unwrap();
+
+        assert!(hub.subscribe(Uuid::new_v4()).is_none());
+
+        drop(second);
+        drop(third);
+        assert_eq!(hub.connections.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn delivers_clicks_only_to_subscribers_of_the_link() {
+        let hub = hub(10);
+        let (watched, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut subscription = hub.subscribe(watched).unwrap();
+
+        hub.publish(vec![click(other), click(watched), click(Uuid::new_v4())]);
+
+        assert_eq!(subscription.receiver.try_recv().unwrap().url_id, watched);
+        assert!(matches!(
+            subscription.receiver.try_recv(),
+            Err(TryRecvError::Empty)
+        ));
+    }
+
+    #[test]
+    fn removes_the_channel_with_the_last_subscriber() {
+        let hub = hub(10);
+        let url_id = Uuid::new_v4();
+        let first = hub.subscribe(url_id).unwrap();
+        let second = hub.subscribe(url_id).unwrap();
+
+        drop(first);
+        assert!(hub.channels.lock().unwrap().contains_key(&url_id));
+
+        drop(second);
+        assert!(hub.channels.lock().unwrap().is_empty());
+    }
+}
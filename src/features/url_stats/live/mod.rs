@@ -0,0 +1,3 @@
+mod live_click_hub;
+
+pub use live_click_hub::*;
@@ -1,6 +1,10 @@
 pub mod entity;
 pub mod error;
+pub mod export;
+pub mod jobs;
+pub mod live;
 pub mod model;
 pub mod queue;
 pub mod repository;
 pub mod service;
+pub mod value_objects;
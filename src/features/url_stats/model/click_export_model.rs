@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+/// One click log as written to exports, flat so it maps onto CSV and Parquet columns.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClickExportRow {
+    pub id: Uuid,
+    pub short_code: String,
+    pub accessed_at: DateTime<Utc>,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub referrer: Option<String>,
+    pub referrer_domain: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub url_revision: Option<i32>,
+}
@@ -0,0 +1,16 @@
+use crate::features::url_stats::{
+    queue::QueuedStatsEvent,
+    value_objects::{AnonymizedIp, GeoLocation, Referrer, UserAgentInfo},
+};
+
+/// A queued click enriched with everything derived from it at processing
+/// time, with personal data already reduced to what the privacy policy allows.
+#[derive(Debug)]
+pub struct ClickRecord<'a> {
+    pub queued: &'a QueuedStatsEvent,
+    pub ip_address: AnonymizedIp,
+    pub user_agent: String,
+    pub referrer: Option<Referrer>,
+    pub agent: UserAgentInfo,
+    pub geo: Option<GeoLocation>,
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::features::url_stats::{
+    model::ClickRecord,
+    value_objects::{DeviceType, ReferrerChannel},
+};
+
+/// A recorded click as pushed to live subscribers, limited to what the
+/// privacy policy let through.
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveClick {
+    #[serde(skip)]
+    pub url_id: Uuid,
+    pub accessed_at: DateTime<Utc>,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub device: DeviceType,
+    pub browser: String,
+    pub is_bot: bool,
+    pub referrer: Option<String>,
+    pub channel: ReferrerChannel,
+}
+
+impl From<&ClickRecord<'_>> for LiveClick {
+    fn from(record: &ClickRecord<'_>) -> Self {
+        let geo = record.geo.as_ref();
+        let domain = record.referrer.as_ref().map(|referrer| referrer.domain());
+
+        Self {
+            url_id: record.queued.event.url_id,
+            accessed_at: record.queued.event.accessed_at,
+            country_code: geo.and_then(|geo| geo.country_code()).map(str::to_string),
+            country: geo.and_then(|geo| geo.country()).map(str::to_string),
+            device: record.agent.device(),
+            browser: record.agent.browser().to_string(),
+            is_bot: record.agent.is_bot(),
+            referrer: domain.map(str::to_string),
+            channel: ReferrerChannel::classify(domain),
+        }
+    }
+}
@@ -1,7 +1,15 @@
+mod click_export_model;
+mod click_record_model;
+mod live_click_model;
 mod url_stats_logs_model;
 mod url_stats_model;
 mod url_stats_report_model;
+mod url_stats_timeseries_model;
 
+pub use click_export_model::*;
+pub use click_record_model::*;
+pub use live_click_model::*;
 pub use url_stats_logs_model::*;
 pub use url_stats_model::*;
 pub use url_stats_report_model::*;
+pub use url_stats_timeseries_model::*;
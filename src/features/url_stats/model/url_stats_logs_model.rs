@@ -8,7 +8,18 @@ pub struct UrlStatsLogsModel {
     pub url_stats_id: Uuid,
     pub ip_address: String,
     pub user_agent: String,
+    pub referrer: Option<String>,
+    pub referrer_domain: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub accessed_at: Option<DateTime<Utc>>,
+    pub url_revision: Option<i32>,
 }
 
 impl UrlStatsLogsModel {
@@ -18,7 +29,18 @@ impl UrlStatsLogsModel {
             url_stats_id,
             ip_address,
             user_agent,
+            referrer: None,
+            referrer_domain: None,
+            browser: None,
+            os: None,
+            device: None,
+            is_bot: false,
+            country_code: None,
+            country: None,
+            region: None,
+            city: None,
             accessed_at: Some(Utc::now()),
+            url_revision: None,
         }
     }
 }
@@ -7,6 +7,7 @@ pub struct UrlStatsModel {
     pub id: Option<Uuid>,
     pub url_id: Uuid,
     pub access_count: i32,
+    pub bot_count: i32,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
@@ -18,6 +19,7 @@ impl UrlStatsModel {
             id: None,
             url_id,
             access_count,
+            bot_count: 0,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
             deleted_at: None,
@@ -25,7 +27,7 @@ impl UrlStatsModel {
     }
 
     pub fn update_access_count(&mut self) {
-        self.access_count = self.access_count + 1;
+        self.access_count += 1;
         self.updated_at = Some(Utc::now())
     }
 }
@@ -1,30 +1,177 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UrlStatsReportModel {
-    pub id: Option<Uuid>,
-    pub original_url: String,
-    pub short_code: String,
-    pub access_count: i32,
-    pub ip_address: String,
-    pub user_agent: String,
-    pub accessed_at: Option<DateTime<Utc>>,
-}
+use crate::features::url_stats::value_objects::{LogCursor, ReferrerChannel};
 
 #[derive(Debug, Serialize)]
-pub struct LogList {
+pub struct UrlStatsReportModel {
     pub id: Uuid,
     pub original_url: String,
     pub short_code: String,
+    /// Human clicks only, link-preview crawlers and other bots are in `bot_count`.
     pub access_count: i32,
+    pub bot_count: i32,
+    /// HyperLogLog estimate of distinct human visitors, `None` when unavailable.
+    pub unique_visitors: Option<u64>,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug)]
+pub struct LogPage {
     pub logs: Vec<Log>,
+    pub next_cursor: Option<LogCursor>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct Log {
     pub ip_address: String,
     pub user_agent: String,
+    pub referrer: Option<String>,
+    pub browser: Option<String>,
+    pub os: Option<String>,
+    pub device: Option<String>,
+    pub is_bot: bool,
+    pub country_code: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
     pub access_at: DateTime<Utc>,
+    /// Destination revision the click was redirected to, `None` for older clicks.
+    pub url_revision: Option<i32>,
+}
+
+/// Clicks from one referring domain, `None` standing for direct traffic.
+#[derive(Debug)]
+pub struct ReferrerCount {
+    pub domain: Option<String>,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferrerReport {
+    pub access_count: i64,
+    pub channels: Vec<ChannelCount>,
+    pub top_domains: Vec<DomainCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelCount {
+    pub channel: ReferrerChannel,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DomainCount {
+    pub domain: String,
+    pub channel: ReferrerChannel,
+    pub access_count: i64,
+}
+
+impl ReferrerReport {
+    pub fn from_counts(counts: Vec<ReferrerCount>, top: usize) -> Self {
+        let mut access_count = 0;
+        let mut channels: BTreeMap<ReferrerChannel, i64> = BTreeMap::new();
+        let mut domains = Vec::new();
+
+        for ReferrerCount {
+            domain,
+            access_count: count,
+        } in counts
+        {
+            let channel = ReferrerChannel::classify(domain.as_deref());
+
+            access_count += count;
+            *channels.entry(channel).or_default() += count;
+
+            if let Some(domain) = domain {
+                domains.push(DomainCount {
+                    domain,
+                    channel,
+                    access_count: count,
+                });
+            }
+        }
+
+        domains.sort_by(|a, b| {
+            b.access_count
+                .cmp(&a.access_count)
+                .then_with(|| a.domain.cmp(&b.domain))
+        });
+        domains.truncate(top);
+
+        Self {
+            access_count,
+            channels: channels
+                .into_iter()
+                .map(|(channel, access_count)| ChannelCount {
+                    channel,
+                    access_count,
+                })
+                .collect(),
+            top_domains: domains,
+        }
+    }
+}
+
+/// Human clicks for one browser, OS and device combination.
+#[derive(Debug)]
+pub struct ClientCount {
+    pub browser: String,
+    pub os: String,
+    pub device: String,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientReport {
+    pub browsers: Vec<NamedCount>,
+    pub operating_systems: Vec<NamedCount>,
+    pub devices: Vec<NamedCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NamedCount {
+    pub name: String,
+    pub access_count: i64,
+}
+
+impl ClientReport {
+    pub fn from_counts(counts: Vec<ClientCount>) -> Self {
+        let mut browsers: BTreeMap<String, i64> = BTreeMap::new();
+        let mut operating_systems: BTreeMap<String, i64> = BTreeMap::new();
+        let mut devices: BTreeMap<String, i64> = BTreeMap::new();
+
+        for count in counts {
+            *browsers.entry(count.browser).or_default() += count.access_count;
+            *operating_systems.entry(count.os).or_default() += count.access_count;
+            *devices.entry(count.device).or_default() += count.access_count;
+        }
+
+        Self {
+            browsers: Self::ranked(browsers),
+            operating_systems: Self::ranked(operating_systems),
+            devices: Self::ranked(devices),
+        }
+    }
+
+    fn ranked(counts: BTreeMap<String, i64>) -> Vec<NamedCount> {
+        let mut ranked: Vec<NamedCount> = counts
+            .into_iter()
+            .map(|(name, access_count)| NamedCount { name, access_count })
+            .collect();
+
+        // Stable sort keeps ties in name order
+        ranked.sort_by_key(|count| std::cmp::Reverse(count.access_count));
+        ranked
+    }
+}
+
+/// Human clicks from one country, `None` when the address couldn't be resolved.
+#[derive(Debug, Serialize)]
+pub struct CountryCount {
+    pub country_code: Option<String>,
+    pub country: Option<String>,
+    pub access_count: i64,
 }
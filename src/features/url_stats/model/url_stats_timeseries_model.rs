@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::features::url_stats::value_objects::TimeseriesInterval;
+
+#[derive(Debug, Serialize)]
+pub struct ClickTimeseries {
+    pub short_code: String,
+    pub interval: TimeseriesInterval,
+    pub tz: Tz,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub buckets: Vec<TimeseriesBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeseriesBucket {
+    /// Start of the bucket, rendered in the requested time zone.
+    pub bucket: DateTime<Tz>,
+    pub access_count: i64,
+    /// `None` when the bucket is older than the sketches are kept, or Redis is unavailable.
+    pub unique_visitors: Option<u64>,
+}
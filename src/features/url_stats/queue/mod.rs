@@ -1,3 +1,9 @@
+mod pg_stats_queue;
+mod stats_event;
 mod stats_processor;
+mod stats_queue;
 
+pub use pg_stats_queue::*;
+pub use stats_event::*;
 pub use stats_processor::*;
+pub use stats_queue::*;
@@ -0,0 +1,143 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    features::url_stats::{
+        error::UrlStatsError,
+        queue::{IStatsQueue, QueuedStatsEvent, StatsEvent},
+    },
+    infrastructure::database::connection::DatabasePool,
+};
+
+/// Outbox table backed queue, claimed with `FOR UPDATE SKIP LOCKED` so several
+/// instances can consume it concurrently.
+pub struct PgStatsQueue {
+    database: Arc<DatabasePool>,
+}
+
+impl PgStatsQueue {
+    pub fn new(database: Arc<DatabasePool>) -> Self {
+        Self { database }
+    }
+}
+
+impl IStatsQueue for PgStatsQueue {
+    async fn enqueue(&self, events: &[StatsEvent]) -> Result<(), UrlStatsError> {
+        let payloads = events
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        sqlx::query!(
+            "INSERT INTO stats_events (payload) SELECT * FROM UNNEST($1::jsonb[])",
+            &payloads
+        )
+        .execute(&self.database.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn claim(
+        &self,
+        limit: i64,
+        visibility_timeout: Duration,
+    ) -> Result<Vec<QueuedStatsEvent>, UrlStatsError> {
+        let rows = sqlx::query!(
+            r#"
+            UPDATE stats_events
+            SET attempts = attempts + 1,
+                available_at = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM stats_events
+                WHERE available_at <= NOW()
+                ORDER BY id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, payload, attempts
+            "#,
+            limit,
+            visibility_timeout.as_secs_f64()
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(QueuedStatsEvent {
+                    id: row.id,
+                    attempts: row.attempts,
+                    event: serde_json::from_value(row.payload)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn ack(&self, ids: &[i64]) -> Result<(), UrlStatsError> {
+        sqlx::query!("DELETE FROM stats_events WHERE id = ANY($1)", ids)
+            .execute(&self.database.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn nack(
+        &self,
+        event: &QueuedStatsEvent,
+        error: &str,
+        retry_in: Duration,
+        max_attempts: i32,
+    ) -> Result<bool, UrlStatsError> {
+        if event.attempts < max_attempts {
+            sqlx::query!(
+                r#"
+                UPDATE stats_events
+                SET last_error = $2, available_at = NOW() + make_interval(secs => $3)
+                WHERE id = $1
+                "#,
+                event.id,
+                error,
+                retry_in.as_secs_f64()
+            )
+            .execute(&self.database.pool)
+            .await?;
+
+            return Ok(false);
+        }
+
+        let mut tx = self.database.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO stats_events_dead_letter (id, payload, attempts, last_error, created_at)
+            SELECT id, payload, attempts, $2, created_at FROM stats_events WHERE id = $1
+            ON CONFLICT (id) DO NOTHING
+            "#,
+            event.id,
+            error
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM stats_events WHERE id = $1", event.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    async fn purge_dead_letters_before(&self, cutoff: DateTime<Utc>) -> Result<u64, UrlStatsError> {
+        let result = sqlx::query!(
+            "DELETE FROM stats_events_dead_letter WHERE failed_at < $1",
+            cutoff
+        )
+        .execute(&self.database.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    configuration::{IpStorage, PrivacySettings},
+    features::{
+        url_stats::{
+            error::UrlStatsError,
+            value_objects::{AnonymizedIp, Referrer, UserAgentInfo, VisitorKey},
+        },
+        urls::models::Url,
+    },
+};
+
+/// A single click on a short link. Events are created with the visitor's full
+/// address and reduced by [`StatsEvent::reduce`] before they are queued, so
+/// neither the queue nor its dead-letter table ever hold a full address unless
+/// full addresses are stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsEvent {
+    pub url_id: Uuid,
+    pub ip_address: AnonymizedIp,
+    /// Truncated address the location is resolved from when `ip_address` is a hash.
+    #[serde(default)]
+    pub network: Option<AnonymizedIp>,
+    pub user_agent: String,
+    #[serde(default)]
+    pub referrer: Option<Referrer>,
+    /// The visitor sent `DNT: 1` or `Sec-GPC: 1`.
+    #[serde(default)]
+    pub do_not_track: bool,
+    /// Derived from the full address and the daily salt before it is reduced.
+    #[serde(default)]
+    pub visitor: Option<VisitorKey>,
+    /// Parsed user agent of opted-out clicks, whose raw header is not queued.
+    #[serde(default)]
+    pub agent: Option<UserAgentInfo>,
+    /// Destination revision the visitor was redirected to.
+    #[serde(default)]
+    pub url_revision: Option<i32>,
+    pub accessed_at: DateTime<Utc>,
+}
+
+impl StatsEvent {
+    pub fn new(
+        url: &Url,
+        ip_address: String,
+        user_agent: String,
+        referrer: Option<Referrer>,
+        do_not_track: bool,
+    ) -> Result<Self, UrlStatsError> {
+        Ok(Self {
+            url_id: url.id.ok_or(UrlStatsError::MissingUrlId)?,
+            ip_address: AnonymizedIp::full(&ip_address),
+            network: None,
+            user_agent,
+            referrer,
+            do_not_track,
+            visitor: None,
+            agent: None,
+            url_revision: url.revision,
+            accessed_at: Utc::now(),
+        })
+    }
+
+    /// Reduces the visitor data to what `privacy` allows. `salt` is the salt
+    /// of the day of the click, hashed addresses are derived from the full one.
+    pub fn reduce(self, privacy: &PrivacySettings, salt: &[u8]) -> Self {
+        if self.do_not_track && privacy.honor_do_not_track {
+            return Self {
+                ip_address: AnonymizedIp::redacted(),
+                network: None,
+                user_agent: String::new(),
+                referrer: None,
+                visitor: None,
+                agent: Some(UserAgentInfo::parse(&self.user_agent)),
+                ..self
+            };
+        }
+
+        let full = self.ip_address.as_str();
+        let visitor = VisitorKey::new(full, &self.user_agent, salt);
+        let (ip_address, network) = match privacy.ip_storage {
+            IpStorage::Full => (self.ip_address.clone(), None),
+            IpStorage::Truncate => (AnonymizedIp::truncate(full), None),
+            IpStorage::Hash => (
+                AnonymizedIp::hash(full, salt),
+                Some(AnonymizedIp::truncate(full)),
+            ),
+        };
+
+        Self {
+            ip_address,
+            network,
+            visitor: Some(visitor),
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::urls::value_objects::{Expiration, ShortCode, ValidUrl};
+
+    fn event(ip_address: &str) -> StatsEvent {
+        let mut url = Url::new(
+            ValidUrl::new(String::from("https://example.com/")).unwrap(),
+            ShortCode::new(String::from("abc123")).unwrap(),
+            Expiration::default(),
+        );
+        url.id = Some(Uuid::new_v4());
+
+        StatsEvent::new(
+            &url,
+            ip_address.to_string(),
+            String::from("Mozilla/5.0"),
+            None,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn privacy(ip_storage: IpStorage) -> PrivacySettings {
+        PrivacySettings {
+            ip_storage,
+            honor_do_not_track: true,
+            retention_days: 0,
+            purge_interval_secs: 3600,
+        }
+    }
+
+    #[test]
+    fn hashes_the_full_address() {
+        let privacy = privacy(IpStorage::Hash);
+        let first = event("203.0.113.7").reduce(&privacy, b"salt");
+        let second = event("203.0.113.8").reduce(&privacy, b"salt");
+
+        assert_ne!(first.ip_address, second.ip_address);
+        assert_eq!(first.ip_address, AnonymizedIp::hash("203.0.113.7", b"salt"));
+        assert_eq!(first.network, Some(AnonymizedIp::truncate("203.0.113.7")));
+    }
+
+    #[test]
+    fn truncates_without_keeping_the_full_address() {
+        let reduced = event("203.0.113.7").reduce(&privacy(IpStorage::Truncate), b"salt");
+
+        assert_eq!(reduced.ip_address.as_str(), "203.0.113.0");
+        assert_eq!(reduced.network, None);
+        assert!(reduced.visitor.is_some());
+    }
+
+    #[test]
+    fn drops_visitor_data_of_opted_out_clicks() {
+        let mut opted_out = event("203.0.113.7");
+        opted_out.do_not_track = true;
+
+        let reduced = opted_out.reduce(&privacy(IpStorage::Full), b"salt");
+
+        assert_eq!(reduced.ip_address, AnonymizedIp::redacted());
+        assert!(reduced.user_agent.is_empty());
+        assert!(reduced.visitor.is_none());
+        assert!(reduced.agent.is_some());
+    }
+}
@@ -1,37 +1,287 @@
-use crate::features::{
-    url_stats::{
+use crate::{
+    configuration::StatsQueueSettings,
+    features::url_stats::{
+        live::LiveClickHub,
+        queue::{IStatsQueue, QueuedStatsEvent, StatsEvent},
         repository::UrlStatsRepository,
         service::{IUrlStatsService, UrlStatsService},
     },
-    urls::models::Url,
+    infrastructure::telemetry::ApplicationMetrics,
 };
-use std::{sync::Arc, time::Instant};
-use tokio::sync::mpsc;
+use std::{marker::PhantomData, sync::Arc};
+use tokio::sync::mpsc::{self, error::TrySendError};
 
-pub struct StatsEvent {
-    pub url: Url,
-    pub ip_address: String,
-    pub user_agent: String,
-    pub timestamp: Instant,
+pub struct StatsProcessor<Q: IStatsQueue> {
+    sender: mpsc::Sender<StatsEvent>,
+    metrics: Arc<ApplicationMetrics>,
+    queue: PhantomData<Q>,
 }
 
-#[derive(Clone)]
-pub struct StatsProcessor {
-    pub sender: mpsc::Sender<StatsEvent>,
+impl<Q: IStatsQueue> Clone for StatsProcessor<Q> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            metrics: self.metrics.clone(),
+            queue: PhantomData,
+        }
+    }
+}
+
+impl<Q: IStatsQueue + 'static> StatsProcessor<Q> {
+    /// Spawns the task moving published events into `queue` and the worker
+    /// draining it, and returns the publishing handle.
+    pub fn new(
+        queue: Arc<Q>,
+        settings: StatsQueueSettings,
+        service: Arc<UrlStatsService<UrlStatsRepository>>,
+        live_clicks: Arc<LiveClickHub>,
+        metrics: Arc<ApplicationMetrics>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(settings.buffer_capacity.max(1));
+        let flusher = StatsFlusher {
+            queue: queue.clone(),
+            service: service.clone(),
+            receiver,
+            batch_size: settings.batch_size.max(1) as usize,
+            metrics: metrics.clone(),
+        };
+        let worker = StatsWorker {
+            queue,
+            settings,
+            service,
+            live_clicks,
+            metrics: metrics.clone(),
+        };
+
+        tokio::spawn(flusher.run());
+        tokio::spawn(worker.run());
+
+        Self {
+            sender,
+            metrics,
+            queue: PhantomData,
+        }
+    }
+
+    /// Hands the event to the flusher without waiting on the database. When
+    /// the buffer is full the event is counted as dropped, the redirect itself
+    /// never fails.
+    pub fn publish(&self, event: StatsEvent) {
+        match self.sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.metrics.increment_stats_dropped();
+                tracing::warn!("Stats buffer is full, dropping event")
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.metrics.increment_stats_dropped();
+                tracing::error!("Stats flusher is gone, dropping event")
+            }
+        }
+    }
+}
+
+/// Reduces buffered events and writes them to the queue table, taking whatever
+/// accumulated while the previous insert ran so busy periods get larger batches.
+struct StatsFlusher<Q: IStatsQueue> {
+    queue: Arc<Q>,
+    service: Arc<UrlStatsService<UrlStatsRepository>>,
+    receiver: mpsc::Receiver<StatsEvent>,
+    batch_size: usize,
+    metrics: Arc<ApplicationMetrics>,
+}
+
+impl<Q: IStatsQueue> StatsFlusher<Q> {
+    async fn run(mut self) {
+        let mut events = Vec::with_capacity(self.batch_size);
+
+        while self.receiver.recv_many(&mut events, self.batch_size).await > 0 {
+            let mut reduced = Vec::with_capacity(events.len());
+
+            for event in events.drain(..) {
+                match self.service.reduce_event(event).await {
+                    Ok(event) => reduced.push(event),
+                    Err(error) => {
+                        self.metrics.increment_stats_dropped();
+                        tracing::error!("Failed to reduce stats event: {}", error)
+                    }
+                }
+            }
+
+            if let Err(error) = self.queue.enqueue(&reduced).await {
+                self.metrics
+                    .increment_stats_dropped_by(reduced.len() as u64);
+                tracing::error!("Failed to queue {} stats events: {}", reduced.len(), error)
+            }
+        }
+    }
+}
+
+struct StatsWorker<Q: IStatsQueue> {
+    queue: Arc<Q>,
+    settings: StatsQueueSettings,
+    service: Arc<UrlStatsService<UrlStatsRepository>>,
+    live_clicks: Arc<LiveClickHub>,
+    metrics: Arc<ApplicationMetrics>,
 }
 
-impl StatsProcessor {
-    pub fn new(capacity: usize, service: Arc<UrlStatsService<UrlStatsRepository>>) -> Self {
-        let (sender, mut receiver) = mpsc::channel::<StatsEvent>(capacity);
+impl<Q: IStatsQueue> StatsWorker<Q> {
+    async fn run(self) {
+        loop {
+            let claimed = self
+                .queue
+                .claim(self.settings.batch_size, self.settings.visibility_timeout())
+                .await;
 
-        tokio::spawn(async move {
-            while let Some(event) = receiver.recv().await {
-                if let Err(error) = service.record_url_access_and_log(event).await {
-                    println!("Failed to record stats: {}", error)
+            match claimed {
+                Ok(events) => {
+                    let is_full = events.len() as i64 >= self.settings.batch_size;
+
+                    if !events.is_empty() {
+                        self.process(events).await;
+                    }
+
+                    // Flush as soon as a batch fills up, otherwise once per interval
+                    if !is_full {
+                        tokio::time::sleep(self.settings.poll_interval()).await
+                    }
                 }
+                Err(error) => {
+                    tracing::error!("Failed to claim stats events: {}", error);
+                    tokio::time::sleep(self.settings.poll_interval()).await
+                }
+            }
+        }
+    }
+
+    async fn process(&self, events: Vec<QueuedStatsEvent>) {
+        let outcome =
+            record_isolating(&events, |batch| self.service.record_url_accesses(batch)).await;
+
+        if !outcome.failed.is_empty() {
+            tracing::warn!(
+                "Failed to record {} of {} stats events",
+                outcome.failed.len(),
+                events.len()
+            );
+        }
+
+        self.live_clicks.publish(outcome.output);
+
+        let ids: Vec<i64> = outcome.recorded.iter().map(|queued| queued.id).collect();
+
+        // Unacked events become visible again after their timeout, and
+        // recording them a second time is a no-op
+        if !ids.is_empty()
+            && let Err(error) = self.queue.ack(&ids).await
+        {
+            tracing::error!("Failed to ack {} stats events: {}", ids.len(), error);
+        }
+
+        for (queued, error) in outcome.failed {
+            self.retry(queued, &error.to_string()).await;
+        }
+    }
+
+    async fn retry(&self, queued: &QueuedStatsEvent, error: &str) {
+        self.metrics.increment_stats_failed();
+
+        let retry_in = self.settings.retry_delay(queued.attempts);
+        let outcome = self
+            .queue
+            .nack(queued, error, retry_in, self.settings.max_attempts)
+            .await;
+
+        match outcome {
+            Ok(true) => self.metrics.increment_stats_dead_lettered(),
+            Ok(false) => {}
+            Err(error) => {
+                tracing::error!("Failed to settle stats event {}: {}", queued.id, error)
+            }
+        }
+    }
+}
+
+/// What became of a batch passed to [`record_isolating`].
+struct Isolated<'a, T, R, E> {
+    output: Vec<R>,
+    recorded: Vec<&'a T>,
+    failed: Vec<(&'a T, E)>,
+}
+
+/// Records `items` in one go, and splits a failing batch in halves until the
+/// items failing on their own are isolated. Those are the only ones reported
+/// as failed, the items batched with them are still recorded.
+async fn record_isolating<'a, T, R, E, F, Fut>(items: &'a [T], record: F) -> Isolated<'a, T, R, E>
+where
+    F: Fn(&'a [T]) -> Fut,
+    Fut: Future<Output = Result<Vec<R>, E>>,
+{
+    let mut outcome = Isolated {
+        output: Vec::new(),
+        recorded: Vec::new(),
+        failed: Vec::new(),
+    };
+    let mut batches = vec![items];
+
+    while let Some(batch) = batches.pop() {
+        match record(batch).await {
+            Ok(output) => {
+                outcome.output.extend(output);
+                outcome.recorded.extend(batch);
             }
-        });
+            Err(error) if batch.len() == 1 => outcome.failed.push((&batch[0], error)),
+            Err(_) => {
+                let (first, second) = batch.split_at(batch.len() / 2);
+                batches.push(second);
+                batches.push(first);
+            }
+        }
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Fails any batch holding a negative item.
+    async fn record(batch: &[i64], calls: &AtomicUsize) -> Result<Vec<i64>, String> {
+        calls.fetch_add(1, Ordering::Relaxed);
+
+        match batch.iter().find(|item| **item < 0) {
+            Some(bad) => Err(format!("bad item {bad}")),
+            None => Ok(batch.iter().map(|item| item * 10).collect()),
+        }
+    }
+
+    #[tokio::test]
+    async fn records_a_good_batch_at_once() {
+        let calls = AtomicUsize::new(0);
+        let items = [1, 2, 3];
+
+        let outcome = record_isolating(&items, |batch| record(batch, &calls)).await;
+
+        assert_eq!(outcome.output, vec![10, 20, 30]);
+        assert_eq!(outcome.recorded.len(), 3);
+        assert!(outcome.failed.is_empty());
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn fails_only_the_bad_event_of_a_batch() {
+        let calls = AtomicUsize::new(0);
+        let items = [1, 2, 3, -4, 5, 6, 7];
+
+        let outcome = record_isolating(&items, |batch| record(batch, &calls)).await;
+        let mut recorded: Vec<i64> = outcome.recorded.into_iter().copied().collect();
+        recorded.sort();
 
-        Self { sender }
+        assert_eq!(recorded, vec![1, 2, 3, 5, 6, 7]);
+        assert_eq!(outcome.output.len(), 6);
+        assert_eq!(outcome.failed, vec![(&-4, String::from("bad item -4"))]);
     }
 }
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::features::url_stats::{error::UrlStatsError, queue::StatsEvent};
+
+/// An event handed out by [`IStatsQueue::claim`]. It stays invisible to other
+/// consumers until it is acked, nacked, or its visibility timeout expires.
+#[derive(Debug, Clone)]
+pub struct QueuedStatsEvent {
+    pub id: i64,
+    pub attempts: i32,
+    pub event: StatsEvent,
+}
+
+/// Durable, at-least-once queue between the redirect handler and the stats writer.
+pub trait IStatsQueue: Send + Sync {
+    fn enqueue(
+        &self,
+        events: &[StatsEvent],
+    ) -> impl Future<Output = Result<(), UrlStatsError>> + Send;
+
+    fn claim(
+        &self,
+        limit: i64,
+        visibility_timeout: Duration,
+    ) -> impl Future<Output = Result<Vec<QueuedStatsEvent>, UrlStatsError>> + Send;
+
+    fn ack(&self, ids: &[i64]) -> impl Future<Output = Result<(), UrlStatsError>> + Send;
+
+    /// Schedules a retry after `retry_in`, or moves the event to the dead-letter
+    /// store once it has been attempted `max_attempts` times. Returns `true`
+    /// when the event was dead-lettered.
+    fn nack(
+        &self,
+        event: &QueuedStatsEvent,
+        error: &str,
+        retry_in: Duration,
+        max_attempts: i32,
+    ) -> impl Future<Output = Result<bool, UrlStatsError>> + Send;
+
+    /// Deletes dead-lettered events that failed before `cutoff`.
+    fn purge_dead_letters_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, UrlStatsError>> + Send;
+}
@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use async_stream::try_stream;
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use futures_util::stream::BoxStream;
+use rand::Rng;
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 use uuid::Uuid;
 
 // new api's
@@ -6,28 +10,108 @@ use uuid::Uuid;
 use crate::{
     features::{
         url_stats::{
-            entity::{UrlStatsEntity, UrlStatsReportEntity},
+            entity::{
+                ClickExportEntity, TimeseriesBucketEntity, UrlStatsLog, UrlStatsReportEntity,
+            },
             error::UrlStatsError,
-            model::{Log, LogList, UrlStatsModel},
-            queue::StatsEvent,
+            model::{
+                ClickExportRow, ClickRecord, ClientCount, CountryCount, Log, LogPage,
+                ReferrerCount, TimeseriesBucket, UrlStatsReportModel,
+            },
+            value_objects::{ExportQuery, LogCursor, LogQuery, TimeseriesRange},
         },
         urls::value_objects::ShortCode,
     },
     infrastructure::database::connection::DatabasePool,
 };
 
+/// Click logs read per query while streaming an export.
+const EXPORT_CHUNK_SIZE: i64 = 1000;
+
 pub trait IUrlStatsRepository: Send + Sync {
-    fn save(
+    /// Records a batch of clicks in one transaction and returns how many were
+    /// new. Events already recorded (redeliveries) and events for deleted
+    /// links are skipped.
+    fn save_batch(
         &self,
-        event: &StatsEvent,
-        access_count: i32,
-    ) -> impl Future<Output = Result<UrlStatsModel, UrlStatsError>> + Send;
+        records: &[ClickRecord<'_>],
+    ) -> impl Future<Output = Result<u64, UrlStatsError>> + Send;
 
-    fn find_one(&self, url_id: Uuid) -> impl Future<Output = Result<i32, UrlStatsError>> + Send;
     fn fetch_stats(
         &self,
         short_code: ShortCode,
-    ) -> impl Future<Output = Result<Option<LogList>, UrlStatsError>> + Send;
+    ) -> impl Future<Output = Result<Option<UrlStatsReportModel>, UrlStatsError>> + Send;
+
+    fn fetch_logs(
+        &self,
+        short_code: &ShortCode,
+        query: &LogQuery,
+    ) -> impl Future<Output = Result<Option<LogPage>, UrlStatsError>> + Send;
+
+    fn fetch_referrer_counts(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Option<Vec<ReferrerCount>>, UrlStatsError>> + Send;
+
+    /// Human click counts per browser, OS and device combination.
+    fn fetch_client_counts(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Option<Vec<ClientCount>>, UrlStatsError>> + Send;
+
+    /// Human click counts per country, unresolved addresses grouped under `None`.
+    fn fetch_country_counts(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Option<Vec<CountryCount>>, UrlStatsError>> + Send;
+
+    /// Returns the salt for `day`, creating it on first use. Concurrent
+    /// workers agree on a single salt per day.
+    fn fetch_daily_salt(
+        &self,
+        day: NaiveDate,
+    ) -> impl Future<Output = Result<Vec<u8>, UrlStatsError>> + Send;
+
+    /// Deletes the salts of days before `day`, making older hashes unlinkable.
+    fn purge_salts_before(
+        &self,
+        day: NaiveDate,
+    ) -> impl Future<Output = Result<u64, UrlStatsError>> + Send;
+
+    /// Deletes click logs recorded before `cutoff`. Counters and rollups are
+    /// left untouched.
+    fn purge_logs_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, UrlStatsError>> + Send;
+
+    /// Recomputes hourly and daily rollups for every bucket touched since the
+    /// last run, minus `lateness` to pick up late-arriving clicks.
+    fn refresh_rollups(
+        &self,
+        lateness: Duration,
+    ) -> impl Future<Output = Result<(), UrlStatsError>> + Send;
+
+    fn find_url_id(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Option<Uuid>, UrlStatsError>> + Send;
+
+    fn fetch_timeseries(
+        &self,
+        url_id: Uuid,
+        range: &TimeseriesRange,
+    ) -> impl Future<Output = Result<Vec<TimeseriesBucket>, UrlStatsError>> + Send;
+
+    /// Streams click logs oldest first, for one link or all of them when
+    /// `url_id` is `None`. Rows are read in keyset-paged chunks as the stream is
+    /// polled, so memory does not grow with the size of the export and no
+    /// connection is held while the client downloads.
+    fn stream_logs(
+        &self,
+        url_id: Option<Uuid>,
+        query: &ExportQuery,
+    ) -> BoxStream<'static, Result<ClickExportRow, UrlStatsError>>;
 }
 
 pub struct UrlStatsRepository {
@@ -41,123 +125,687 @@ impl UrlStatsRepository {
 }
 
 impl IUrlStatsRepository for UrlStatsRepository {
-    async fn find_one(&self, url_id: Uuid) -> Result<i32, UrlStatsError> {
-        let response = sqlx::query!(
-            "SELECT access_count FROM url_stats WHERE url_id = $1",
+    async fn save_batch(&self, records: &[ClickRecord<'_>]) -> Result<u64, UrlStatsError> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let mut event_ids = Vec::with_capacity(records.len());
+        let mut url_ids = Vec::with_capacity(records.len());
+        let mut ip_addresses = Vec::with_capacity(records.len());
+        let mut user_agents = Vec::with_capacity(records.len());
+        let mut referrers = Vec::with_capacity(records.len());
+        let mut referrer_domains = Vec::with_capacity(records.len());
+        let mut browsers = Vec::with_capacity(records.len());
+        let mut operating_systems = Vec::with_capacity(records.len());
+        let mut devices = Vec::with_capacity(records.len());
+        let mut bots = Vec::with_capacity(records.len());
+        let mut country_codes = Vec::with_capacity(records.len());
+        let mut countries = Vec::with_capacity(records.len());
+        let mut regions = Vec::with_capacity(records.len());
+        let mut cities = Vec::with_capacity(records.len());
+        let mut accessed_at = Vec::with_capacity(records.len());
+        let mut url_revisions = Vec::with_capacity(records.len());
+
+        for record in records {
+            let ClickRecord {
+                queued,
+                agent,
+                referrer,
+                ..
+            } = record;
+            let geo = record.geo.as_ref();
+
+            event_ids.push(queued.id);
+            url_ids.push(queued.event.url_id);
+            ip_addresses.push(record.ip_address.as_str().to_string());
+            user_agents.push(record.user_agent.clone());
+            referrers.push(referrer.as_ref().map(|r| r.url().to_string()));
+            referrer_domains.push(referrer.as_ref().map(|r| r.domain().to_string()));
+            browsers.push(agent.browser().to_string());
+            operating_systems.push(agent.os().to_string());
+            devices.push(agent.device().as_str().to_string());
+            bots.push(agent.is_bot());
+            country_codes.push(geo.and_then(|geo| geo.country_code()).map(str::to_string));
+            countries.push(geo.and_then(|geo| geo.country()).map(str::to_string));
+            regions.push(geo.and_then(|geo| geo.region()).map(str::to_string));
+            cities.push(geo.and_then(|geo| geo.city()).map(str::to_string));
+            accessed_at.push(queued.event.accessed_at);
+            url_revisions.push(queued.event.url_revision);
+        }
+
+        let mut tx = self.database.pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            WITH events AS (
+                SELECT e.*
+                FROM UNNEST(
+                    $1::bigint[], $2::uuid[], $3::text[], $4::text[], $5::timestamptz[],
+                    $6::text[], $7::text[], $8::text[], $9::text[], $10::text[], $11::bool[],
+                    $12::text[], $13::text[], $14::text[], $15::text[], $16::int[]
+                ) AS e(
+                    event_id, url_id, ip_address, user_agent, accessed_at, referrer,
+                    referrer_domain, browser, os, device, is_bot, country_code, country,
+                    region, city, url_revision
+                )
+                WHERE EXISTS (SELECT 1 FROM urls WHERE urls.id = e.url_id)
+            ),
+            stats AS (
+                INSERT INTO url_stats (url_id)
+                SELECT DISTINCT url_id FROM events ORDER BY url_id
+                ON CONFLICT (url_id) DO UPDATE SET updated_at = NOW()
+                RETURNING id, url_id
+            )
+            INSERT INTO url_stats_logs (
+                event_id, url_stats_id, ip_address, user_agent, accessed_at, referrer,
+                referrer_domain, browser, os, device, is_bot, country_code, country, region, city,
+                url_revision
+            )
+            SELECT events.event_id, stats.id, events.ip_address, events.user_agent,
+                   events.accessed_at, events.referrer, events.referrer_domain,
+                   events.browser, events.os, events.device, events.is_bot,
+                   events.country_code, events.country, events.region, events.city,
+                   events.url_revision
+            FROM events
+            JOIN stats ON stats.url_id = events.url_id
+            ON CONFLICT (event_id) DO NOTHING
+            RETURNING url_stats_id, is_bot
+            "#,
+            &event_ids,
+            &url_ids,
+            &ip_addresses,
+            &user_agents,
+            &accessed_at,
+            &referrers as &[Option<String>],
+            &referrer_domains as &[Option<String>],
+            &browsers,
+            &operating_systems,
+            &devices,
+            &bots,
+            &country_codes as &[Option<String>],
+            &countries as &[Option<String>],
+            &regions as &[Option<String>],
+            &cities as &[Option<String>],
+            &url_revisions as &[Option<i32>]
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        // (human clicks, bot hits) per url_stats row
+        let mut increments: BTreeMap<Uuid, (i32, i32)> = BTreeMap::new();
+
+        for row in &inserted {
+            let entry = increments.entry(row.url_stats_id).or_default();
+
+            if row.is_bot {
+                entry.1 += 1;
+            } else {
+                entry.0 += 1;
+            }
+        }
+
+        // Sorted ids keep the row lock order stable across concurrent workers
+        let mut stats_ids = Vec::with_capacity(increments.len());
+        let mut clicks = Vec::with_capacity(increments.len());
+        let mut bot_hits = Vec::with_capacity(increments.len());
+
+        for (stats_id, (human, bot)) in increments {
+            stats_ids.push(stats_id);
+            clicks.push(human);
+            bot_hits.push(bot);
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE url_stats
+            SET access_count = url_stats.access_count + batch.clicks,
+                bot_count = url_stats.bot_count + batch.bots,
+                updated_at = NOW()
+            FROM UNNEST($1::uuid[], $2::int[], $3::int[]) AS batch(id, clicks, bots)
+            WHERE url_stats.id = batch.id
+            "#,
+            &stats_ids,
+            &clicks,
+            &bot_hits
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(inserted.len() as u64)
+    }
+
+    async fn fetch_stats(
+        &self,
+        short_code: ShortCode,
+    ) -> Result<Option<UrlStatsReportModel>, UrlStatsError> {
+        let report = sqlx::query_as!(
+            UrlStatsReportEntity,
+            r#"
+            SELECT
+              url.id,
+              url.original_url,
+              url.short_code,
+              COALESCE(stats.access_count, 0) AS "access_count!",
+              COALESCE(stats.bot_count, 0) AS "bot_count!",
+              (
+                SELECT MAX(logs.accessed_at)
+                FROM url_stats_logs logs
+                WHERE logs.url_stats_id = stats.id AND NOT logs.is_bot
+              ) AS last_accessed_at
+            FROM urls url
+            LEFT JOIN url_stats stats ON stats.url_id = url.id
+            WHERE url.short_code = $1
+            "#,
+            short_code.as_str(),
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        Ok(report.map(UrlStatsReportEntity::to_domain))
+    }
+
+    async fn fetch_logs(
+        &self,
+        short_code: &ShortCode,
+        query: &LogQuery,
+    ) -> Result<Option<LogPage>, UrlStatsError> {
+        let url = sqlx::query!(
+            r#"
+            SELECT url.id, stats.id AS "stats_id?"
+            FROM urls url
+            LEFT JOIN url_stats stats ON stats.url_id = url.id
+            WHERE url.short_code = $1
+            "#,
+            short_code.as_str()
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        let Some(url) = url else {
+            return Ok(None);
+        };
+
+        let Some(stats_id) = url.stats_id else {
+            return Ok(Some(LogPage {
+                logs: Vec::new(),
+                next_cursor: None,
+            }));
+        };
+
+        let cursor = query.cursor();
+
+        // One extra row tells whether another page follows
+        let mut rows = sqlx::query_as!(
+            UrlStatsLog,
+            r#"
+            SELECT logs.id, logs.url_stats_id, logs.ip_address, logs.user_agent,
+                   logs.referrer, logs.referrer_domain, logs.browser, logs.os, logs.device,
+                   logs.is_bot, logs.country_code, logs.country, logs.region, logs.city, logs.accessed_at,
+                   logs.url_revision
+            FROM url_stats_logs logs
+            WHERE logs.url_stats_id = $1
+              AND ($2::timestamptz IS NULL OR (logs.accessed_at, logs.id) < ($2, $3::uuid))
+              AND ($4::timestamptz IS NULL OR logs.accessed_at >= $4)
+              AND ($5::timestamptz IS NULL OR logs.accessed_at < $5)
+              AND ($6::text IS NULL OR logs.ip_address = $6)
+              AND ($7::text IS NULL OR logs.user_agent ILIKE $7)
+              AND ($8::bool IS NULL OR logs.is_bot = $8)
+            ORDER BY logs.accessed_at DESC, logs.id DESC
+            LIMIT $9
+            "#,
+            stats_id,
+            cursor.map(|cursor| cursor.accessed_at()),
+            cursor.map(|cursor| cursor.id()),
+            query.from(),
+            query.to(),
+            query.ip_address(),
+            query.user_agent_pattern(),
+            query.is_bot(),
+            query.limit() + 1
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        let has_more = rows.len() as i64 > query.limit();
+        rows.truncate(query.limit() as usize);
+
+        let next_cursor = rows
+            .last()
+            .filter(|_| has_more)
+            .map(|row| LogCursor::new(row.accessed_at, row.id));
+
+        Ok(Some(LogPage {
+            logs: rows
+                .into_iter()
+                .map(|row| Log {
+                    ip_address: row.ip_address,
+                    user_agent: row.user_agent,
+                    referrer: row.referrer,
+                    browser: row.browser,
+                    os: row.os,
+                    device: row.device,
+                    is_bot: row.is_bot,
+                    country_code: row.country_code,
+                    region: row.region,
+                    city: row.city,
+                    access_at: row.accessed_at,
+                    url_revision: row.url_revision,
+                })
+                .collect(),
+            next_cursor,
+        }))
+    }
+
+    async fn fetch_referrer_counts(
+        &self,
+        short_code: &ShortCode,
+    ) -> Result<Option<Vec<ReferrerCount>>, UrlStatsError> {
+        let url_id = sqlx::query_scalar!(
+            "SELECT id FROM urls WHERE short_code = $1",
+            short_code.as_str()
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        let Some(url_id) = url_id else {
+            return Ok(None);
+        };
+
+        let counts = sqlx::query!(
+            r#"
+            SELECT logs.referrer_domain, COUNT(*) AS "access_count!"
+            FROM url_stats_logs logs
+            JOIN url_stats stats ON stats.id = logs.url_stats_id
+            WHERE stats.url_id = $1 AND NOT logs.is_bot
+            GROUP BY logs.referrer_domain
+            "#,
             url_id
         )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(Some(
+            counts
+                .into_iter()
+                .map(|row| ReferrerCount {
+                    domain: row.referrer_domain,
+                    access_count: row.access_count,
+                })
+                .collect(),
+        ))
+    }
+
+    async fn fetch_client_counts(
+        &self,
+        short_code: &ShortCode,
+    ) -> Result<Option<Vec<ClientCount>>, UrlStatsError> {
+        let url_id = sqlx::query_scalar!(
+            "SELECT id FROM urls WHERE short_code = $1",
+            short_code.as_str()
+        )
         .fetch_optional(&self.database.pool)
         .await?;
 
-        if let Some(record) = response {
-            return Ok(record.access_count);
-        }
+        let Some(url_id) = url_id else {
+            return Ok(None);
+        };
+
+        let counts = sqlx::query_as!(
+            ClientCount,
+            r#"
+            SELECT
+              COALESCE(logs.browser, 'Unknown') AS "browser!",
+              COALESCE(logs.os, 'Unknown') AS "os!",
+              COALESCE(logs.device, 'other') AS "device!",
+              COUNT(*) AS "access_count!"
+            FROM url_stats_logs logs
+            JOIN url_stats stats ON stats.id = logs.url_stats_id
+            WHERE stats.url_id = $1 AND NOT logs.is_bot
+            GROUP BY 1, 2, 3
+            "#,
+            url_id
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
 
-        Ok(0)
+        Ok(Some(counts))
     }
 
-    async fn save(
+    async fn fetch_country_counts(
         &self,
-        event: &StatsEvent,
-        access_count: i32,
-    ) -> Result<UrlStatsModel, UrlStatsError> {
-        let url_id = event.url.id.ok_or(UrlStatsError::MissingUrlId)?;
-        let mut tx = self.database.pool.begin().await?;
+        short_code: &ShortCode,
+    ) -> Result<Option<Vec<CountryCount>>, UrlStatsError> {
+        let url_id = sqlx::query_scalar!(
+            "SELECT id FROM urls WHERE short_code = $1",
+            short_code.as_str()
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
 
-        let result = async {
-            let response = sqlx::query_as!(
-                UrlStatsEntity,
-                r#"
-                INSERT INTO url_stats (url_id, access_count)
-                VALUES ($1, $2)
-                ON CONFLICT (url_id) DO UPDATE
-                SET access_count = EXCLUDED.access_count,
-                    updated_at = NOW()
-                RETURNING id as "id!",
-                          url_id as "url_id!",
-                          access_count as "access_count!",
-                          created_at as "created_at!",
-                          updated_at as "updated_at!",
-                          deleted_at
-                "#,
-                url_id,
-                access_count
-            )
-            .fetch_one(&mut *tx)
+        let Some(url_id) = url_id else {
+            return Ok(None);
+        };
+
+        let counts = sqlx::query_as!(
+            CountryCount,
+            r#"
+            SELECT logs.country_code, MAX(logs.country) AS country, COUNT(*) AS "access_count!"
+            FROM url_stats_logs logs
+            JOIN url_stats stats ON stats.id = logs.url_stats_id
+            WHERE stats.url_id = $1 AND NOT logs.is_bot
+            GROUP BY logs.country_code
+            ORDER BY 3 DESC, 1
+            "#,
+            url_id
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(Some(counts))
+    }
+
+    async fn fetch_daily_salt(&self, day: NaiveDate) -> Result<Vec<u8>, UrlStatsError> {
+        let salt: [u8; 32] = rand::rng().random();
+
+        sqlx::query!(
+            "INSERT INTO ip_hash_salts (day, salt) VALUES ($1, $2) ON CONFLICT (day) DO NOTHING",
+            day,
+            &salt[..]
+        )
+        .execute(&self.database.pool)
+        .await?;
+
+        let salt = sqlx::query_scalar!("SELECT salt FROM ip_hash_salts WHERE day = $1", day)
+            .fetch_one(&self.database.pool)
             .await?;
 
-            sqlx::query!(
+        Ok(salt)
+    }
+
+    async fn purge_salts_before(&self, day: NaiveDate) -> Result<u64, UrlStatsError> {
+        let result = sqlx::query!("DELETE FROM ip_hash_salts WHERE day < $1", day)
+            .execute(&self.database.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge_logs_before(&self, cutoff: DateTime<Utc>) -> Result<u64, UrlStatsError> {
+        let mut purged = 0;
+
+        // Small batches keep each delete short and avoid long row locks
+        loop {
+            let result = sqlx::query!(
                 r#"
-                INSERT INTO url_stats_logs (url_stats_id, ip_address, user_agent)
-                VALUES ($1, $2, $3)
+                DELETE FROM url_stats_logs
+                WHERE id IN (
+                    SELECT id FROM url_stats_logs WHERE accessed_at < $1 LIMIT 5000
+                )
                 "#,
-                response.id,
-                event.ip_address,
-                event.user_agent
+                cutoff
             )
-            .execute(&mut *tx)
+            .execute(&self.database.pool)
             .await?;
 
-            Ok::<UrlStatsEntity, UrlStatsError>(response)
-        }
-        .await;
+            purged += result.rows_affected();
 
-        match result {
-            Ok(response) => {
-                tx.commit().await?;
-                Ok(response.to_domain())
-            }
-            Err(e) => {
-                tx.rollback().await?;
-                Err(e)
+            if result.rows_affected() < 5000 {
+                return Ok(purged);
             }
         }
     }
 
-    async fn fetch_stats(&self, short_code: ShortCode) -> Result<Option<LogList>, UrlStatsError> {
-        let response = sqlx::query_as!(
-            UrlStatsReportEntity,
+    async fn refresh_rollups(&self, lateness: Duration) -> Result<(), UrlStatsError> {
+        let started_at = Utc::now();
+        let mut tx = self.database.pool.begin().await?;
+
+        // Only one instance refreshes at a time, the others skip this round
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_xact_lock(hashtext('url_stats_rollup')) AS "locked!""#
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !locked {
+            return Ok(());
+        }
+
+        let rolled_up_to = sqlx::query_scalar!(
+            "SELECT rolled_up_to FROM stats_rollup_state WHERE name = 'url_stats'"
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let lateness = TimeDelta::from_std(lateness).unwrap_or(TimeDelta::MAX);
+        let since = rolled_up_to.map(|date| date - lateness);
+
+        sqlx::query!(
             r#"
-            SELECT
-              url.id AS id,
-              url.original_url,
-              url.short_code,
-              stats.access_count,
-              logs.ip_address,
-              logs.user_agent,
-              logs.accessed_at
-            FROM urls url
-            JOIN url_stats stats ON stats.url_id = url.id
-            LEFT JOIN url_stats_logs logs ON logs.url_stats_id = stats.id
-            WHERE url.short_code = $1;
+            INSERT INTO url_stats_hourly (url_id, bucket, clicks)
+            SELECT stats.url_id, date_trunc('hour', logs.accessed_at, 'UTC'), COUNT(*)
+            FROM url_stats_logs logs
+            JOIN url_stats stats ON stats.id = logs.url_stats_id
+            WHERE NOT logs.is_bot
+              AND (
+                $1::timestamptz IS NULL
+                OR logs.accessed_at >= date_trunc('hour', $1::timestamptz, 'UTC')
+              )
+            GROUP BY 1, 2
+            ON CONFLICT (url_id, bucket) DO UPDATE SET clicks = EXCLUDED.clicks
             "#,
-            short_code.as_str(),
+            since
         )
-        .fetch_all(&self.database.pool)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO url_stats_daily (url_id, bucket, clicks)
+            SELECT url_id, date_trunc('day', bucket, 'UTC'), SUM(clicks)
+            FROM url_stats_hourly
+            WHERE $1::timestamptz IS NULL
+               OR bucket >= date_trunc('day', $1::timestamptz, 'UTC')
+            GROUP BY 1, 2
+            ON CONFLICT (url_id, bucket) DO UPDATE SET clicks = EXCLUDED.clicks
+            "#,
+            since
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO stats_rollup_state (name, rolled_up_to)
+            VALUES ('url_stats', $1)
+            ON CONFLICT (name) DO UPDATE SET rolled_up_to = EXCLUDED.rolled_up_to
+            "#,
+            started_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn find_url_id(&self, short_code: &ShortCode) -> Result<Option<Uuid>, UrlStatsError> {
+        let url_id = sqlx::query_scalar!(
+            "SELECT id FROM urls WHERE short_code = $1",
+            short_code.as_str()
+        )
+        .fetch_optional(&self.database.pool)
         .await?;
 
-        if let Some(stat) = response.first() {
-            let mut capt = LogList {
-                id: stat.id,
-                original_url: stat.original_url.clone(),
-                short_code: stat.short_code.clone(),
-                access_count: stat.access_count,
-                logs: [].to_vec(),
-            };
-
-            for stat in response {
-                let value = Log {
-                    ip_address: stat.ip_address,
-                    user_agent: stat.user_agent,
-                    access_at: stat.accessed_at,
+        Ok(url_id)
+    }
+
+    async fn fetch_timeseries(
+        &self,
+        url_id: Uuid,
+        range: &TimeseriesRange,
+    ) -> Result<Vec<TimeseriesBucket>, UrlStatsError> {
+        let interval = range.interval().as_str();
+        let tz = range.tz().name();
+
+        // Buckets are generated in local wall time so days and weeks follow the
+        // requested zone, then joined against the rollups to zero-fill gaps.
+        // Rollups are first limited to the span of the buckets, so only the
+        // requested range is read from the `(url_id, bucket)` index
+        let buckets = if range.uses_daily_rollup() {
+            sqlx::query_as!(
+                TimeseriesBucketEntity,
+                r#"
+                WITH buckets AS (
+                    SELECT generate_series(
+                        date_trunc($2, $3::timestamptz AT TIME ZONE $5),
+                        date_trunc($2, ($4::timestamptz - interval '1 microsecond') AT TIME ZONE $5),
+                        ('1 ' || $2)::interval
+                    ) AS local_bucket
+                )
+                SELECT (buckets.local_bucket AT TIME ZONE $5) AS "bucket!",
+                       COALESCE(SUM(daily.clicks), 0)::bigint AS "access_count!"
+                FROM buckets
+                LEFT JOIN url_stats_daily daily
+                    ON daily.url_id = $1
+                   AND daily.bucket >= date_trunc($2, $3::timestamptz AT TIME ZONE $5) AT TIME ZONE $5
+                   AND daily.bucket < (date_trunc($2, ($4::timestamptz - interval '1 microsecond') AT TIME ZONE $5)
+                                     + ('1 ' || $2)::interval) AT TIME ZONE $5
+                   AND date_trunc($2, daily.bucket AT TIME ZONE $5) = buckets.local_bucket
+                GROUP BY buckets.local_bucket
+                ORDER BY buckets.local_bucket
+                "#,
+                url_id,
+                interval,
+                range.from(),
+                range.to(),
+                tz
+            )
+            .fetch_all(&self.database.pool)
+            .await?
+        } else {
+            sqlx::query_as!(
+                TimeseriesBucketEntity,
+                r#"
+                WITH buckets AS (
+                    SELECT generate_series(
+                        date_trunc($2, $3::timestamptz AT TIME ZONE $5),
+                        date_trunc($2, ($4::timestamptz - interval '1 microsecond') AT TIME ZONE $5),
+                        ('1 ' || $2)::interval
+                    ) AS local_bucket
+                )
+                SELECT (buckets.local_bucket AT TIME ZONE $5) AS "bucket!",
+                       COALESCE(SUM(hourly.clicks), 0)::bigint AS "access_count!"
+                FROM buckets
+                LEFT JOIN url_stats_hourly hourly
+                    ON hourly.url_id = $1
+                   AND hourly.bucket >= date_trunc($2, $3::timestamptz AT TIME ZONE $5) AT TIME ZONE $5
+                   AND hourly.bucket < (date_trunc($2, ($4::timestamptz - interval '1 microsecond') AT TIME ZONE $5)
+                                     + ('1 ' || $2)::interval) AT TIME ZONE $5
+                   AND date_trunc($2, hourly.bucket AT TIME ZONE $5) = buckets.local_bucket
+                GROUP BY buckets.local_bucket
+                ORDER BY buckets.local_bucket
+                "#,
+                url_id,
+                interval,
+                range.from(),
+                range.to(),
+                tz
+            )
+            .fetch_all(&self.database.pool)
+            .await?
+        };
+
+        Ok(buckets
+            .iter()
+            .map(|bucket| bucket.to_domain(range.tz()))
+            .collect())
+    }
+
+    fn stream_logs(
+        &self,
+        url_id: Option<Uuid>,
+        query: &ExportQuery,
+    ) -> BoxStream<'static, Result<ClickExportRow, UrlStatsError>> {
+        let pool = self.database.pool.clone();
+        let (from, to) = (query.from(), query.to());
+
+        Box::pin(try_stream! {
+            let mut after: Option<(DateTime<Utc>, Uuid)> = None;
+
+            loop {
+                let (after_at, after_id) = after.unzip();
+                let chunk = match url_id {
+                    Some(url_id) => {
+                        sqlx::query_as!(
+                            ClickExportEntity,
+                            r#"
+                            SELECT logs.id, url.short_code, logs.accessed_at, logs.ip_address,
+                                   logs.user_agent, logs.referrer, logs.referrer_domain, logs.browser,
+                                   logs.os, logs.device, logs.is_bot, logs.country_code, logs.country,
+                                   logs.region, logs.city, logs.url_revision
+                            FROM url_stats stats
+                            JOIN urls url ON url.id = stats.url_id
+                            JOIN url_stats_logs logs ON logs.url_stats_id = stats.id
+                            WHERE stats.url_id = $1
+                              AND ($2::timestamptz IS NULL OR logs.accessed_at >= $2)
+                              AND ($3::timestamptz IS NULL OR logs.accessed_at < $3)
+                              AND ($4::timestamptz IS NULL OR (logs.accessed_at, logs.id) > ($4, $5))
+                            ORDER BY logs.accessed_at, logs.id
+                            LIMIT $6
+                            "#,
+                            url_id,
+                            from,
+                            to,
+                            after_at,
+                            after_id,
+                            EXPORT_CHUNK_SIZE
+                        )
+                        .fetch_all(&pool)
+                        .await?
+                    }
+                    None => {
+                        sqlx::query_as!(
+                            ClickExportEntity,
+                            r#"
+                            SELECT logs.id, url.short_code, logs.accessed_at, logs.ip_address,
+                                   logs.user_agent, logs.referrer, logs.referrer_domain, logs.browser,
+                                   logs.os, logs.device, logs.is_bot, logs.country_code, logs.country,
+                                   logs.region, logs.city, logs.url_revision
+                            FROM url_stats_logs logs
+                            JOIN url_stats stats ON stats.id = logs.url_stats_id
+                            JOIN urls url ON url.id = stats.url_id
+                            WHERE ($1::timestamptz IS NULL OR logs.accessed_at >= $1)
+                              AND ($2::timestamptz IS NULL OR logs.accessed_at < $2)
+                              AND ($3::timestamptz IS NULL OR (logs.accessed_at, logs.id) > ($3, $4))
+                            ORDER BY logs.accessed_at, logs.id
+                            LIMIT $5
+                            "#,
+                            from,
+                            to,
+                            after_at,
+                            after_id,
+                            EXPORT_CHUNK_SIZE
+                        )
+                        .fetch_all(&pool)
+                        .await?
+                    }
                 };
 
-                capt.logs.push(value);
-            }
+                let exhausted = chunk.len() < EXPORT_CHUNK_SIZE as usize;
+                after = chunk.last().map(|row| (row.accessed_at, row.id));
 
-            return Ok(Some(capt));
-        }
+                for row in chunk {
+                    yield row.to_domain();
+                }
 
-        Ok(None)
+                if exhausted {
+                    break;
+                }
+            }
+        })
     }
 }
@@ -1,52 +1,437 @@
-use std::sync::Arc;
-
-use crate::features::{
-    url_stats::{
-        error::UrlStatsError,
-        model::{LogList, UrlStatsModel},
-        queue::StatsEvent,
-        repository::IUrlStatsRepository,
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, DurationRound, NaiveDate, TimeDelta, Utc};
+use uuid::Uuid;
+
+use crate::{
+    configuration::{PrivacySettings, UniqueVisitorSettings},
+    features::{
+        url_stats::{
+            error::UrlStatsError,
+            export::{ExportEncoder, ExportStream},
+            model::{
+                ClickRecord, ClickTimeseries, ClientReport, CountryCount, LiveClick, LogPage,
+                ReferrerReport, TimeseriesBucket, UrlStatsReportModel,
+            },
+            queue::{QueuedStatsEvent, StatsEvent},
+            repository::IUrlStatsRepository,
+            value_objects::{
+                AnonymizedIp, ExportQuery, LogQuery, TimeseriesRange, UserAgentInfo, VisitorKey,
+            },
+        },
+        urls::value_objects::ShortCode,
     },
-    urls::value_objects::ShortCode,
+    infrastructure::{cache::redis_cache::RedisCache, geoip::geoip_resolver::GeoIpResolver},
 };
 
 pub trait IUrlStatsService: Send + Sync {
-    fn record_url_access_and_log(
+    /// Reduces a click's visitor data before it is queued, with the salt of
+    /// the day it happened.
+    fn reduce_event(
         &self,
         event: StatsEvent,
-    ) -> impl Future<Output = Result<UrlStatsModel, UrlStatsError>> + Send;
+    ) -> impl Future<Output = Result<StatsEvent, UrlStatsError>> + Send;
+    /// Records a batch of clicks and returns them as published to live subscribers.
+    fn record_url_accesses(
+        &self,
+        events: &[QueuedStatsEvent],
+    ) -> impl Future<Output = Result<Vec<LiveClick>, UrlStatsError>> + Send;
+    fn find_url_id(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Option<Uuid>, UrlStatsError>> + Send;
     fn fetch_stats(
         &self,
         short_code: ShortCode,
-    ) -> impl Future<Output = Result<Option<LogList>, UrlStatsError>> + Send;
+    ) -> impl Future<Output = Result<Option<UrlStatsReportModel>, UrlStatsError>> + Send;
+    fn fetch_logs(
+        &self,
+        short_code: ShortCode,
+        query: LogQuery,
+    ) -> impl Future<Output = Result<Option<LogPage>, UrlStatsError>> + Send;
+    fn fetch_referrers(
+        &self,
+        short_code: ShortCode,
+        top: usize,
+    ) -> impl Future<Output = Result<Option<ReferrerReport>, UrlStatsError>> + Send;
+    fn fetch_clients(
+        &self,
+        short_code: ShortCode,
+    ) -> impl Future<Output = Result<Option<ClientReport>, UrlStatsError>> + Send;
+    fn fetch_countries(
+        &self,
+        short_code: ShortCode,
+    ) -> impl Future<Output = Result<Option<Vec<CountryCount>>, UrlStatsError>> + Send;
+    fn fetch_timeseries(
+        &self,
+        short_code: ShortCode,
+        range: TimeseriesRange,
+    ) -> impl Future<Output = Result<Option<ClickTimeseries>, UrlStatsError>> + Send;
+    /// Streams click logs of one link encoded in the requested format, `None`
+    /// when the short code is unknown.
+    fn export_logs(
+        &self,
+        short_code: ShortCode,
+        query: ExportQuery,
+    ) -> impl Future<Output = Result<Option<ExportStream>, UrlStatsError>> + Send;
+    /// Streams click logs of every link encoded in the requested format.
+    fn export_all_logs(&self, query: ExportQuery) -> ExportStream;
+    fn refresh_rollups(
+        &self,
+        lateness: Duration,
+    ) -> impl Future<Output = Result<(), UrlStatsError>> + Send;
+    /// Applies the retention policy, returns the number of deleted click logs.
+    fn purge_expired(&self) -> impl Future<Output = Result<u64, UrlStatsError>> + Send;
 }
 
 #[derive(Clone)]
 pub struct UrlStatsService<T> {
     repository: Arc<T>,
+    geoip: Option<Arc<GeoIpResolver>>,
+    privacy: PrivacySettings,
+    salts: Arc<Mutex<HashMap<NaiveDate, Vec<u8>>>>,
+    cache: Arc<RedisCache>,
+    unique_visitors: UniqueVisitorSettings,
 }
 
 impl<T: IUrlStatsRepository> UrlStatsService<T> {
-    pub fn new(repository: Arc<T>) -> Self {
-        Self { repository }
+    /// Clicks are stored without geo fields when no GeoIP database is configured.
+    pub fn new(
+        repository: Arc<T>,
+        geoip: Option<Arc<GeoIpResolver>>,
+        privacy: PrivacySettings,
+        cache: Arc<RedisCache>,
+        unique_visitors: UniqueVisitorSettings,
+    ) -> Self {
+        Self {
+            repository,
+            geoip,
+            privacy,
+            salts: Arc::new(Mutex::new(HashMap::new())),
+            cache,
+            unique_visitors,
+        }
+    }
+
+    fn total_sketch_key(url_id: Uuid) -> String {
+        format!("uv:{url_id}")
+    }
+
+    fn hourly_sketch_key(url_id: Uuid, hour: DateTime<Utc>) -> String {
+        format!("uv:{url_id}:h:{}", hour.format("%Y%m%d%H"))
+    }
+
+    fn daily_sketch_key(url_id: Uuid, day: NaiveDate) -> String {
+        format!("uv:{url_id}:d:{}", day.format("%Y%m%d"))
+    }
+
+    /// Feeds visitors into the all-time, hourly and daily sketches of their
+    /// link. Adding to a sketch is idempotent so retried batches do not inflate
+    /// the estimates, and failures are only logged to keep Redis optional.
+    async fn track_unique_visitors(&self, visitors: &[(Uuid, DateTime<Utc>, VisitorKey)]) {
+        let mut total: HashMap<String, Vec<String>> = HashMap::new();
+        let mut hourly: HashMap<String, Vec<String>> = HashMap::new();
+        let mut daily: HashMap<String, Vec<String>> = HashMap::new();
+
+        for (url_id, accessed_at, visitor) in visitors {
+            let hour = accessed_at.duration_trunc(TimeDelta::hours(1)).unwrap();
+
+            total
+                .entry(Self::total_sketch_key(*url_id))
+                .or_default()
+                .push(visitor.as_str().to_string());
+            hourly
+                .entry(Self::hourly_sketch_key(*url_id, hour))
+                .or_default()
+                .push(visitor.as_str().to_string());
+            daily
+                .entry(Self::daily_sketch_key(*url_id, accessed_at.date_naive()))
+                .or_default()
+                .push(visitor.as_str().to_string());
+        }
+
+        let sketches = [
+            (total, None),
+            (hourly, self.unique_visitors.hourly_ttl().to_std().ok()),
+            (daily, self.unique_visitors.daily_ttl().to_std().ok()),
+        ];
+
+        for (sketch, ttl) in sketches {
+            let sketch: Vec<_> = sketch.into_iter().collect();
+
+            if let Err(error) = self.cache.pf_add(&sketch, ttl).await {
+                tracing::warn!("Failed to track unique visitors: {}", error);
+                return;
+            }
+        }
+    }
+
+    /// Fills `unique_visitors` on each bucket from the sketches overlapping it.
+    /// UTC day and week buckets read daily sketches, everything else reads
+    /// hourly ones, which line up with local buckets since the range only
+    /// accepts zones a whole number of hours away from UTC.
+    async fn count_unique_visitors(
+        &self,
+        url_id: Uuid,
+        range: &TimeseriesRange,
+        buckets: &mut [TimeseriesBucket],
+    ) {
+        let now = Utc::now();
+        let daily = range.uses_daily_rollup();
+        let kept_since = now
+            - if daily {
+                self.unique_visitors.daily_ttl()
+            } else {
+                self.unique_visitors.hourly_ttl()
+            };
+
+        let starts: Vec<DateTime<Utc>> = buckets
+            .iter()
+            .map(|bucket| bucket.bucket.with_timezone(&Utc))
+            .collect();
+
+        let mut counted = Vec::new();
+        let mut groups = Vec::new();
+
+        for (index, start) in starts.iter().enumerate() {
+            // Older buckets have lost some of their sketches and would undercount
+            if *start < kept_since {
+                continue;
+            }
+
+            let end = starts
+                .get(index + 1)
+                .copied()
+                .unwrap_or(*start + range.interval().duration());
+
+            let mut keys = Vec::new();
+
+            if daily {
+                let mut day = start.date_naive();
+
+                while day < end.date_naive() {
+                    keys.push(Self::daily_sketch_key(url_id, day));
+                    day += TimeDelta::days(1);
+                }
+            } else {
+                let mut hour = start.duration_trunc(TimeDelta::hours(1)).unwrap();
+
+                while hour < end {
+                    keys.push(Self::hourly_sketch_key(url_id, hour));
+                    hour += TimeDelta::hours(1);
+                }
+            }
+
+            counted.push(index);
+            groups.push(keys);
+        }
+
+        match self.cache.pf_count(&groups).await {
+            Ok(counts) => {
+                for (index, count) in counted.into_iter().zip(counts) {
+                    buckets[index].unique_visitors = Some(count);
+                }
+            }
+            Err(error) => tracing::warn!("Failed to count unique visitors: {}", error),
+        }
+    }
+
+    async fn daily_salt(&self, day: NaiveDate) -> Result<Vec<u8>, UrlStatsError> {
+        if let Some(salt) = self.salts.lock().unwrap().get(&day) {
+            return Ok(salt.clone());
+        }
+
+        let salt = self.repository.fetch_daily_salt(day).await?;
+
+        let mut salts = self.salts.lock().unwrap();
+        salts.retain(|cached, _| *cached >= day - TimeDelta::days(1));
+        salts.insert(day, salt.clone());
+
+        Ok(salt)
     }
 }
 
 impl<T: IUrlStatsRepository> IUrlStatsService for UrlStatsService<T> {
-    async fn record_url_access_and_log(
+    async fn reduce_event(&self, event: StatsEvent) -> Result<StatsEvent, UrlStatsError> {
+        let salt = self.daily_salt(event.accessed_at.date_naive()).await?;
+
+        Ok(event.reduce(&self.privacy, &salt))
+    }
+
+    async fn record_url_accesses(
         &self,
-        event: StatsEvent,
-    ) -> Result<UrlStatsModel, UrlStatsError> {
-        let url_id = event.url.id.unwrap();
-        let mut access_count = self.repository.find_one(url_id).await?;
-        access_count = access_count + 1;
+        events: &[QueuedStatsEvent],
+    ) -> Result<Vec<LiveClick>, UrlStatsError> {
+        let mut records = Vec::with_capacity(events.len());
+        let mut visitors = Vec::new();
+
+        for queued in events {
+            let event = &queued.event;
+            let agent = event
+                .agent
+                .clone()
+                .unwrap_or_else(|| UserAgentInfo::parse(&event.user_agent));
+
+            // Opted-out clicks still count, but nothing about the visitor is kept
+            if event.do_not_track && self.privacy.honor_do_not_track {
+                records.push(ClickRecord {
+                    queued,
+                    ip_address: AnonymizedIp::redacted(),
+                    user_agent: String::new(),
+                    referrer: None,
+                    agent,
+                    geo: None,
+                });
+                continue;
+            }
+
+            if let Some(visitor) = &event.visitor
+                && !agent.is_bot()
+            {
+                visitors.push((event.url_id, event.accessed_at, visitor.clone()));
+            }
+
+            // Geo is resolved from the queued address, truncated at most to its /24 or /48
+            let geo = self.geoip.as_ref().and_then(|geoip| {
+                geoip.lookup(event.network.as_ref().unwrap_or(&event.ip_address).as_str())
+            });
+
+            records.push(ClickRecord {
+                queued,
+                ip_address: event.ip_address.clone(),
+                user_agent: event.user_agent.clone(),
+                referrer: event.referrer.clone(),
+                agent,
+                geo,
+            });
+        }
+
+        self.repository.save_batch(&records).await?;
+        self.track_unique_visitors(&visitors).await;
+
+        Ok(records.iter().map(LiveClick::from).collect())
+    }
+
+    async fn find_url_id(&self, short_code: &ShortCode) -> Result<Option<Uuid>, UrlStatsError> {
+        self.repository.find_url_id(short_code).await
+    }
+
+    async fn fetch_stats(
+        &self,
+        short_code: ShortCode,
+    ) -> Result<Option<UrlStatsReportModel>, UrlStatsError> {
+        let Some(mut report) = self.repository.fetch_stats(short_code).await? else {
+            return Ok(None);
+        };
+
+        match self
+            .cache
+            .pf_count(&[vec![Self::total_sketch_key(report.id)]])
+            .await
+        {
+            Ok(counts) => report.unique_visitors = counts.first().copied(),
+            Err(error) => tracing::warn!("Failed to count unique visitors: {}", error),
+        }
 
-        self.repository.save(&event, access_count).await
+        Ok(Some(report))
     }
 
-    async fn fetch_stats(&self, short_code: ShortCode) -> Result<Option<LogList>, UrlStatsError> {
-        let response = self.repository.fetch_stats(short_code).await;
+    async fn fetch_logs(
+        &self,
+        short_code: ShortCode,
+        query: LogQuery,
+    ) -> Result<Option<LogPage>, UrlStatsError> {
+        self.repository.fetch_logs(&short_code, &query).await
+    }
+
+    async fn fetch_referrers(
+        &self,
+        short_code: ShortCode,
+        top: usize,
+    ) -> Result<Option<ReferrerReport>, UrlStatsError> {
+        let counts = self.repository.fetch_referrer_counts(&short_code).await?;
+
+        Ok(counts.map(|counts| ReferrerReport::from_counts(counts, top)))
+    }
+
+    async fn fetch_clients(
+        &self,
+        short_code: ShortCode,
+    ) -> Result<Option<ClientReport>, UrlStatsError> {
+        let counts = self.repository.fetch_client_counts(&short_code).await?;
+
+        Ok(counts.map(ClientReport::from_counts))
+    }
+
+    async fn fetch_countries(
+        &self,
+        short_code: ShortCode,
+    ) -> Result<Option<Vec<CountryCount>>, UrlStatsError> {
+        self.repository.fetch_country_counts(&short_code).await
+    }
+
+    async fn fetch_timeseries(
+        &self,
+        short_code: ShortCode,
+        range: TimeseriesRange,
+    ) -> Result<Option<ClickTimeseries>, UrlStatsError> {
+        let Some(url_id) = self.repository.find_url_id(&short_code).await? else {
+            return Ok(None);
+        };
+
+        let mut buckets = self.repository.fetch_timeseries(url_id, &range).await?;
+        self.count_unique_visitors(url_id, &range, &mut buckets)
+            .await;
+
+        Ok(Some(ClickTimeseries {
+            short_code: short_code.as_str().to_string(),
+            interval: range.interval(),
+            tz: range.tz(),
+            from: range.from(),
+            to: range.to(),
+            buckets,
+        }))
+    }
+
+    async fn export_logs(
+        &self,
+        short_code: ShortCode,
+        query: ExportQuery,
+    ) -> Result<Option<ExportStream>, UrlStatsError> {
+        let Some(url_id) = self.repository.find_url_id(&short_code).await? else {
+            return Ok(None);
+        };
+
+        let rows = self.repository.stream_logs(Some(url_id), &query);
+
+        Ok(Some(ExportEncoder::encode(query.format(), rows)))
+    }
+
+    fn export_all_logs(&self, query: ExportQuery) -> ExportStream {
+        let rows = self.repository.stream_logs(None, &query);
+
+        ExportEncoder::encode(query.format(), rows)
+    }
+
+    async fn refresh_rollups(&self, lateness: Duration) -> Result<(), UrlStatsError> {
+        self.repository.refresh_rollups(lateness).await
+    }
+
+    async fn purge_expired(&self) -> Result<u64, UrlStatsError> {
+        let now = Utc::now();
+
+        // Yesterday's salt is kept for late or retried events
+        self.repository
+            .purge_salts_before(now.date_naive() - TimeDelta::days(1))
+            .await?;
 
-        response
+        match self.privacy.retention() {
+            Some(retention) => self.repository.purge_logs_before(now - retention).await,
+            None => Ok(0),
+        }
     }
 }
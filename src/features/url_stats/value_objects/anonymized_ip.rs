@@ -0,0 +1,100 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Visitor address as it is allowed to be stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnonymizedIp(String);
+
+impl AnonymizedIp {
+    /// Keeps the address untouched, for deployments without privacy constraints.
+    pub fn full(ip_address: &str) -> Self {
+        Self(ip_address.to_string())
+    }
+
+    /// Zeroes the host part, keeping the /24 of IPv4 and the /48 of IPv6.
+    /// Unparsable values are stored as `unknown`.
+    pub fn truncate(ip_address: &str) -> Self {
+        let Some(ip) = Self::parse(ip_address) else {
+            return Self(String::from("unknown"));
+        };
+
+        let truncated = match ip {
+            IpAddr::V4(v4) => {
+                let [a, b, c, _] = v4.octets();
+                IpAddr::V4(Ipv4Addr::new(a, b, c, 0))
+            }
+            IpAddr::V6(v6) => {
+                let [a, b, c, ..] = v6.segments();
+                IpAddr::V6(Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0))
+            }
+        };
+
+        Self(truncated.to_string())
+    }
+
+    /// Replaces the address with a salted hash. With a salt that rotates daily
+    /// and is then destroyed, visitors can be counted within a day but not
+    /// linked across days or reversed.
+    pub fn hash(ip_address: &str, salt: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(ip_address.trim().as_bytes());
+
+        // 32 hex chars keep it within the 40 character column
+        Self(hex::encode(&hasher.finalize()[..16]))
+    }
+
+    /// Stored for clicks from visitors who opted out of tracking.
+    pub fn redacted() -> Self {
+        Self(String::new())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn parse(ip_address: &str) -> Option<IpAddr> {
+        ip_address
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .ok()
+    }
+}
+
+impl From<AnonymizedIp> for String {
+    fn from(ip: AnonymizedIp) -> Self {
+        ip.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_ipv4_to_24_and_ipv6_to_48() {
+        assert_eq!(
+            AnonymizedIp::truncate("203.0.113.77").as_str(),
+            "203.0.113.0"
+        );
+        assert_eq!(
+            AnonymizedIp::truncate("2001:db8:85a3:8d3:1319:8a2e:370:7348").as_str(),
+            "2001:db8:85a3::"
+        );
+        assert_eq!(AnonymizedIp::truncate("unknown").as_str(), "unknown");
+    }
+
+    #[test]
+    fn hash_depends_on_salt_and_fits_the_column() {
+        let today = AnonymizedIp::hash("203.0.113.77", b"today");
+
+        assert_eq!(today, AnonymizedIp::hash("203.0.113.77", b"today"));
+        assert_ne!(today, AnonymizedIp::hash("203.0.113.77", b"tomorrow"));
+        assert_ne!(today, AnonymizedIp::hash("203.0.113.78", b"today"));
+        assert_eq!(today.as_str().len(), 32);
+    }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::features::url_stats::error::UrlStatsError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Ndjson,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// Format and optional `[from, to)` window of a click log export.
+#[derive(Debug, Clone)]
+pub struct ExportQuery {
+    format: ExportFormat,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+impl ExportQuery {
+    /// Defaults to CSV over the whole retained history.
+    pub fn new(
+        format: Option<ExportFormat>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Self, UrlStatsError> {
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err(UrlStatsError::InvalidQuery(
+                "`from` must be earlier than `to`".into(),
+            ));
+        }
+
+        Ok(Self {
+            format: format.unwrap_or_default(),
+            from,
+            to,
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    pub fn from(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+
+    pub fn to(&self) -> Option<DateTime<Utc>> {
+        self.to
+    }
+
+    /// Download name, e.g. `clicks-abc123.csv` or `clicks.parquet` for all links.
+    pub fn file_name(&self, short_code: Option<&str>) -> String {
+        match short_code {
+            Some(code) => format!("clicks-{code}.{}", self.format.extension()),
+            None => format!("clicks.{}", self.format.extension()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn defaults_to_an_unbounded_csv_export() {
+        let query = ExportQuery::new(None, None, None).unwrap();
+
+        assert_eq!(query.format(), ExportFormat::Csv);
+        assert!(query.from().is_none() && query.to().is_none());
+        assert_eq!(query.file_name(Some("abc123")), "clicks-abc123.csv");
+    }
+
+    #[test]
+    fn rejects_inverted_windows() {
+        let now = Utc::now();
+
+        assert!(ExportQuery::new(None, Some(now), Some(now - Duration::hours(1))).is_err());
+        assert!(ExportQuery::new(Some(ExportFormat::Parquet), Some(now), None).is_ok());
+    }
+}
@@ -0,0 +1,44 @@
+/// Where a click came from, as resolved from its IP address.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoLocation {
+    country_code: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+}
+
+impl GeoLocation {
+    pub fn new(
+        country_code: Option<String>,
+        country: Option<String>,
+        region: Option<String>,
+        city: Option<String>,
+    ) -> Self {
+        Self {
+            country_code: country_code.map(|code| code.to_uppercase()),
+            country,
+            region,
+            city,
+        }
+    }
+
+    pub fn country_code(&self) -> Option<&str> {
+        self.country_code.as_deref()
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    pub fn city(&self) -> Option<&str> {
+        self.city.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
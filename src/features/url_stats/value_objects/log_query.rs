@@ -0,0 +1,190 @@
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::features::url_stats::error::UrlStatsError;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+
+/// Opaque keyset position, the last `(accessed_at, id)` returned by a page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogCursor {
+    accessed_at: DateTime<Utc>,
+    id: Uuid,
+}
+
+impl LogCursor {
+    pub fn new(accessed_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { accessed_at, id }
+    }
+
+    pub fn decode(value: &str) -> Result<Self, UrlStatsError> {
+        let invalid = || UrlStatsError::InvalidQuery("Invalid cursor".into());
+
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(value)
+            .map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (accessed_at, id) = raw.split_once('|').ok_or_else(invalid)?;
+
+        Ok(Self {
+            accessed_at: DateTime::parse_from_rfc3339(accessed_at)
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!("{}|{}", self.accessed_at.to_rfc3339(), self.id);
+        general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn accessed_at(&self) -> DateTime<Utc> {
+        self.accessed_at
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+/// Validated filters and page position for browsing click logs, newest first.
+#[derive(Debug, Clone)]
+pub struct LogQuery {
+    limit: i64,
+    cursor: Option<LogCursor>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    is_bot: Option<bool>,
+}
+
+impl LogQuery {
+    pub fn new(
+        limit: Option<i64>,
+        cursor: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+        is_bot: Option<bool>,
+    ) -> Result<Self, UrlStatsError> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(UrlStatsError::InvalidQuery(format!(
+                "`limit` must be between 1 and {MAX_LIMIT}"
+            )));
+        }
+
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err(UrlStatsError::InvalidQuery(
+                "`from` must be earlier than `to`".into(),
+            ));
+        }
+
+        let cursor = cursor.as_deref().map(LogCursor::decode).transpose()?;
+
+        Ok(Self {
+            limit,
+            cursor,
+            from,
+            to,
+            ip_address: ip_address.filter(|ip| !ip.is_empty()),
+            user_agent: user_agent.filter(|agent| !agent.is_empty()),
+            is_bot,
+        })
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    pub fn cursor(&self) -> Option<LogCursor> {
+        self.cursor
+    }
+
+    pub fn from(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+
+    pub fn to(&self) -> Option<DateTime<Utc>> {
+        self.to
+    }
+
+    pub fn ip_address(&self) -> Option<&str> {
+        self.ip_address.as_deref()
+    }
+
+    pub fn is_bot(&self) -> Option<bool> {
+        self.is_bot
+    }
+
+    /// `ILIKE` pattern matching the user-agent filter as a literal substring.
+    pub fn user_agent_pattern(&self) -> Option<String> {
+        self.user_agent.as_ref().map(|agent| {
+            let escaped = agent
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = LogCursor::new(Utc::now(), Uuid::new_v4());
+
+        assert_eq!(LogCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(LogCursor::decode("not-a-cursor").is_err());
+    }
+
+    #[test]
+    fn validates_limit_and_range() {
+        let now = Utc::now();
+
+        assert_eq!(
+            LogQuery::new(None, None, None, None, None, None, None)
+                .unwrap()
+                .limit(),
+            DEFAULT_LIMIT
+        );
+        assert!(LogQuery::new(Some(0), None, None, None, None, None, None).is_err());
+        assert!(LogQuery::new(Some(MAX_LIMIT + 1), None, None, None, None, None, None).is_err());
+        assert!(
+            LogQuery::new(
+                None,
+                None,
+                Some(now),
+                Some(now - Duration::hours(1)),
+                None,
+                None,
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn user_agent_filter_is_a_literal_substring() {
+        let query =
+            LogQuery::new(None, None, None, None, None, Some("50%_off\\".into()), None).unwrap();
+
+        assert_eq!(
+            query.user_agent_pattern().as_deref(),
+            Some("%50\\%\\_off\\\\%")
+        );
+    }
+}
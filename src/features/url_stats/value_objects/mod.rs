@@ -0,0 +1,17 @@
+mod anonymized_ip;
+mod export_query;
+mod geo_location;
+mod log_query;
+mod referrer;
+mod timeseries_range;
+mod user_agent_info;
+mod visitor_key;
+
+pub use anonymized_ip::*;
+pub use export_query::*;
+pub use geo_location::*;
+pub use log_query::*;
+pub use referrer::*;
+pub use timeseries_range::*;
+pub use user_agent_info::*;
+pub use visitor_key::*;
@@ -0,0 +1,195 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use url::Url as ParseUrl;
+
+const MAX_REFERRER_LENGTH: usize = 2048;
+
+lazy_static! {
+    /// Hosts of search result pages, matched exactly so other services of the
+    /// same company (`docs.google.com`, `mail.yahoo.com`) aren't counted as search.
+    static ref SEARCH_HOSTS: Vec<&'static str> = vec![
+        "bing.com", "cn.bing.com", "duckduckgo.com", "search.yahoo.com", "baidu.com",
+        "yandex.ru", "ecosia.org", "startpage.com", "qwant.com", "search.brave.com",
+    ];
+    /// Search engines with a site per country, such as `google.de` or `google.co.uk`.
+    static ref COUNTRY_SEARCH_ENGINES: Vec<&'static str> = vec!["google", "yandex"];
+    /// Matched against the end of the referring domain.
+    static ref SOCIAL_NETWORKS: Vec<&'static str> = vec![
+        "facebook.com", "instagram.com", "twitter.com", "x.com", "t.co", "linkedin.com",
+        "lnkd.in", "reddit.com", "pinterest.com", "tiktok.com", "youtube.com", "youtu.be",
+        "threads.net", "mastodon.social", "bsky.app", "t.me", "whatsapp.com", "discord.com",
+    ];
+}
+
+/// Where a click came from, normalized from the `Referer` header.
+///
+/// The query string and fragment are dropped since they often carry
+/// personal data, and the domain loses its `www.`/`m.` prefix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Referrer {
+    url: String,
+    domain: String,
+}
+
+impl Referrer {
+    /// Returns `None` for empty or unparsable headers, which count as direct traffic.
+    pub fn parse(header: &str) -> Option<Self> {
+        let mut parsed = ParseUrl::parse(header.trim()).ok()?;
+        let host = parsed.host_str()?.trim_end_matches('.').to_lowercase();
+
+        let domain = host
+            .strip_prefix("www.")
+            .or_else(|| host.strip_prefix("m."))
+            .unwrap_or(&host)
+            .to_string();
+
+        if domain.is_empty() {
+            return None;
+        }
+
+        parsed.set_query(None);
+        parsed.set_fragment(None);
+        let _ = parsed.set_username("");
+        let _ = parsed.set_password(None);
+
+        let mut url = String::from(parsed);
+        if url.len() > MAX_REFERRER_LENGTH {
+            let mut end = MAX_REFERRER_LENGTH;
+            while !url.is_char_boundary(end) {
+                end -= 1;
+            }
+            url.truncate(end);
+        }
+
+        Some(Self { url, domain })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferrerChannel {
+    Direct,
+    Search,
+    Social,
+    Other,
+}
+
+impl ReferrerChannel {
+    pub fn classify(domain: Option<&str>) -> Self {
+        let Some(domain) = domain else {
+            return Self::Direct;
+        };
+
+        if Self::is_search(domain) {
+            return Self::Search;
+        }
+
+        let is_social = SOCIAL_NETWORKS.iter().any(|network| {
+            domain == *network
+                || domain
+                    .strip_suffix(network)
+                    .is_some_and(|prefix| prefix.ends_with('.'))
+        });
+
+        if is_social { Self::Social } else { Self::Other }
+    }
+
+    fn is_search(domain: &str) -> bool {
+        if SEARCH_HOSTS.contains(&domain) {
+            return true;
+        }
+
+        let Some((engine, suffix)) = domain.split_once('.') else {
+            return false;
+        };
+
+        // A top-level domain, optionally behind `co.` or `com.` as in `google.com.br`
+        let country = suffix
+            .strip_prefix("co.")
+            .or_else(|| suffix.strip_prefix("com."))
+            .unwrap_or(suffix);
+
+        COUNTRY_SEARCH_ENGINES.contains(&engine) && !country.contains('.')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_domain_and_strips_query_string() {
+        let referrer =
+            Referrer::parse("https://user:pw@WWW.Example.com/path?email=a@b.c#top").unwrap();
+
+        assert_eq!(referrer.domain(), "example.com");
+        assert_eq!(referrer.url(), "https://www.example.com/path");
+        assert_eq!(
+            Referrer::parse("https://m.facebook.com/").unwrap().domain(),
+            "facebook.com"
+        );
+    }
+
+    #[test]
+    fn empty_or_invalid_headers_are_direct() {
+        assert!(Referrer::parse("").is_none());
+        assert!(Referrer::parse("not a url").is_none());
+        assert_eq!(ReferrerChannel::classify(None), ReferrerChannel::Direct);
+    }
+
+    #[test]
+    fn classifies_search_social_and_other() {
+        assert_eq!(
+            ReferrerChannel::classify(Some("google.co.uk")),
+            ReferrerChannel::Search
+        );
+        for search in [
+            "google.com",
+            "google.com.br",
+            "bing.com",
+            "search.yahoo.com",
+        ] {
+            assert_eq!(
+                ReferrerChannel::classify(Some(search)),
+                ReferrerChannel::Search,
+                "{search}"
+            );
+        }
+        for other in [
+            "docs.google.com",
+            "maps.google.com",
+            "mail.yahoo.com",
+            "google.blogspot.com",
+        ] {
+            assert_eq!(
+                ReferrerChannel::classify(Some(other)),
+                ReferrerChannel::Other,
+                "{other}"
+            );
+        }
+        assert_eq!(
+            ReferrerChannel::classify(Some("l.facebook.com")),
+            ReferrerChannel::Social
+        );
+        assert_eq!(
+            ReferrerChannel::classify(Some("t.co")),
+            ReferrerChannel::Social
+        );
+        assert_eq!(
+            ReferrerChannel::classify(Some("notx.com")),
+            ReferrerChannel::Other
+        );
+        assert_eq!(
+            ReferrerChannel::classify(Some("blog.example.com")),
+            ReferrerChannel::Other
+        );
+    }
+}
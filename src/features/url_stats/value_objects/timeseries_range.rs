@@ -0,0 +1,202 @@
+use chrono::{DateTime, Duration, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::features::url_stats::error::UrlStatsError;
+
+const MAX_BUCKETS: i64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeseriesInterval {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl TimeseriesInterval {
+    /// Field name understood by Postgres' `date_trunc`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        }
+    }
+}
+
+/// Validated `[from, to)` window for a click time series.
+#[derive(Debug, Clone)]
+pub struct TimeseriesRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    interval: TimeseriesInterval,
+    tz: Tz,
+}
+
+impl TimeseriesRange {
+    /// Defaults to the last 30 days in daily UTC buckets.
+    pub fn new(
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        interval: Option<TimeseriesInterval>,
+        tz: Option<String>,
+    ) -> Result<Self, UrlStatsError> {
+        let interval = interval.unwrap_or_default();
+        let to = to.unwrap_or_else(Utc::now);
+        let from = from.unwrap_or(to - Duration::days(30));
+
+        let tz = match tz {
+            Some(name) => name
+                .parse::<Tz>()
+                .map_err(|_| UrlStatsError::InvalidQuery(format!("Unknown time zone: {name}")))?,
+            None => Tz::UTC,
+        };
+
+        if from >= to {
+            return Err(UrlStatsError::InvalidQuery(
+                "`from` must be earlier than `to`".into(),
+            ));
+        }
+
+        let buckets = (to - from).num_seconds() / interval.duration().num_seconds();
+
+        if buckets > MAX_BUCKETS {
+            return Err(UrlStatsError::InvalidQuery(format!(
+                "Range is too large for {} buckets (max {MAX_BUCKETS})",
+                interval.as_str()
+            )));
+        }
+
+        if !Self::has_whole_hour_offsets(tz, from, to) {
+            return Err(UrlStatsError::InvalidQuery(format!(
+                "Time zone {} is not a whole number of hours from UTC over this range, \
+                 which hourly rollups can't be bucketed in",
+                tz.name()
+            )));
+        }
+
+        Ok(Self {
+            from,
+            to,
+            interval,
+            tz,
+        })
+    }
+
+    /// Offsets only change at transitions, a daily sample catches every one
+    /// that lasts a day or more.
+    fn has_whole_hour_offsets(tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        let is_whole_hour = |at: DateTime<Utc>| {
+            tz.offset_from_utc_datetime(&at.naive_utc())
+                .fix()
+                .local_minus_utc()
+                % 3600
+                == 0
+        };
+
+        let mut at = from;
+
+        while at < to {
+            if !is_whole_hour(at) {
+                return false;
+            }
+            at += Duration::days(1);
+        }
+
+        is_whole_hour(to)
+    }
+
+    pub fn from(&self) -> DateTime<Utc> {
+        self.from
+    }
+
+    pub fn to(&self) -> DateTime<Utc> {
+        self.to
+    }
+
+    pub fn interval(&self) -> TimeseriesInterval {
+        self.interval
+    }
+
+    pub fn tz(&self) -> Tz {
+        self.tz
+    }
+
+    /// Daily rollups are stored in UTC, other zones are derived from hourly rollups.
+    pub fn uses_daily_rollup(&self) -> bool {
+        self.interval != TimeseriesInterval::Hour && self.tz == Tz::UTC
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_thirty_daily_utc_buckets() {
+        let range = TimeseriesRange::new(None, None, None, None).unwrap();
+
+        assert_eq!(range.interval(), TimeseriesInterval::Day);
+        assert_eq!(range.tz(), Tz::UTC);
+        assert_eq!((range.to() - range.from()).num_days(), 30);
+        assert!(range.uses_daily_rollup());
+    }
+
+    #[test]
+    fn rejects_unknown_zones_inverted_and_oversized_ranges() {
+        let now = Utc::now();
+
+        assert!(TimeseriesRange::new(None, None, None, Some("Mars/Olympus".into())).is_err());
+        assert!(
+            TimeseriesRange::new(Some(now), Some(now - Duration::hours(1)), None, None).is_err()
+        );
+        assert!(
+            TimeseriesRange::new(
+                Some(now - Duration::days(3650)),
+                Some(now),
+                Some(TimeseriesInterval::Hour),
+                None
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_zones_with_partial_hour_offsets() {
+        // Crosses the spring DST change of both hemispheres' zones below
+        let from = "2026-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let to = "2026-04-30T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let range =
+            |zone: &str| TimeseriesRange::new(Some(from), Some(to), None, Some(zone.into()));
+
+        for zone in ["Asia/Kolkata", "Asia/Kathmandu", "Australia/Lord_Howe"] {
+            assert!(range(zone).is_err(), "{zone} should be rejected");
+        }
+
+        assert!(range("Europe/Berlin").is_ok());
+        assert!(range("America/New_York").is_ok());
+    }
+
+    #[test]
+    fn non_utc_zones_use_hourly_rollups() {
+        let range = TimeseriesRange::new(
+            None,
+            None,
+            Some(TimeseriesInterval::Week),
+            Some("Africa/Lagos".into()),
+        )
+        .unwrap();
+
+        assert!(!range.uses_daily_rollup());
+    }
+}
@@ -0,0 +1,172 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use woothee::parser::Parser;
+
+const UNKNOWN: &str = "Unknown";
+
+/// Phone makers whose model names end in `bot`.
+const DEVICE_BRANDS: [&str; 1] = ["cubot"];
+
+lazy_static! {
+    static ref PARSER: Parser = Parser::new();
+    /// Link unfurlers, HTTP libraries and headless browsers that woothee
+    /// reports as regular clients.
+    static ref BOT_PATTERN: Regex = Regex::new(
+        r"(?i)crawler|spider|slurp|preview|facebookexternalhit|facebookcatalog|embedly|whatsapp|skypeuripreview|bitlybot|vkshare|curl/|wget/|httpie/|python-requests|python-urllib|aiohttp|go-http-client|okhttp|axios/|node-fetch|libwww-perl|headlesschrome|phantomjs|lighthouse"
+    )
+    .unwrap();
+    /// Words ending in `bot` such as `Googlebot` or `Slackbot-LinkExpanding`.
+    /// `_` counts as a word character, so device models like `CUBOT_NOTE` don't match.
+    static ref BOT_WORD: Regex = Regex::new(r"(?i)\b\w*bot\b").unwrap();
+    static ref TABLET_PATTERN: Regex = Regex::new(r"(?i)ipad|tablet|kindle|silk/|playbook").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceType {
+    Desktop,
+    Mobile,
+    Tablet,
+    Bot,
+    Other,
+}
+
+impl DeviceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Desktop => "desktop",
+            Self::Mobile => "mobile",
+            Self::Tablet => "tablet",
+            Self::Bot => "bot",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Structured view of a `User-Agent` header.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserAgentInfo {
+    browser: String,
+    os: String,
+    device: DeviceType,
+    is_bot: bool,
+}
+
+impl UserAgentInfo {
+    pub fn parse(user_agent: &str) -> Self {
+        let parsed = PARSER.parse(user_agent);
+        let field = |value: Option<&str>| {
+            value
+                .filter(|value| !value.is_empty() && *value != woothee::woothee::VALUE_UNKNOWN)
+                .unwrap_or(UNKNOWN)
+                .to_string()
+        };
+
+        let browser = field(parsed.as_ref().map(|result| result.name));
+        let os = field(parsed.as_ref().map(|result| result.os));
+        let category = parsed.as_ref().map(|result| result.category);
+
+        let is_bot = user_agent.trim().is_empty()
+            || user_agent == "unknown"
+            || category == Some("crawler")
+            || BOT_PATTERN.is_match(user_agent)
+            || BOT_WORD
+                .find_iter(user_agent)
+                .any(|word| !DEVICE_BRANDS.contains(&word.as_str().to_lowercase().as_str()));
+
+        let device = if is_bot {
+            DeviceType::Bot
+        } else if TABLET_PATTERN.is_match(user_agent)
+            || (user_agent.contains("Android") && !user_agent.contains("Mobile"))
+        {
+            DeviceType::Tablet
+        } else {
+            match category {
+                Some("pc") => DeviceType::Desktop,
+                Some("smartphone") | Some("mobilephone") => DeviceType::Mobile,
+                _ => DeviceType::Other,
+            }
+        };
+
+        Self {
+            browser,
+            os,
+            device,
+            is_bot,
+        }
+    }
+
+    pub fn browser(&self) -> &str {
+        &self.browser
+    }
+
+    pub fn os(&self) -> &str {
+        &self.os
+    }
+
+    pub fn device(&self) -> DeviceType {
+        self.device
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.is_bot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_desktop_and_mobile_browsers() {
+        let desktop = UserAgentInfo::parse(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36",
+        );
+        assert_eq!(desktop.browser(), "Chrome");
+        assert_eq!(desktop.os(), "Windows 10");
+        assert_eq!(desktop.device(), DeviceType::Desktop);
+        assert!(!desktop.is_bot());
+
+        let iphone = UserAgentInfo::parse(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(iphone.browser(), "Safari");
+        assert_eq!(iphone.device(), DeviceType::Mobile);
+
+        let ipad = UserAgentInfo::parse(
+            "Mozilla/5.0 (iPad; CPU OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1",
+        );
+        assert_eq!(ipad.device(), DeviceType::Tablet);
+    }
+
+    #[test]
+    fn flags_unfurlers_and_http_clients_as_bots() {
+        for agent in [
+            "Slackbot-LinkExpanding 1.0 (+https://api.slack.com/robots)",
+            "Twitterbot/1.0",
+            "facebookexternalhit/1.1 (+http://www.facebook.com/externalhit_uatext.php)",
+            "curl/8.5.0",
+            "python-requests/2.32.3",
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            "unknown",
+            "",
+        ] {
+            let info = UserAgentInfo::parse(agent);
+            assert!(info.is_bot(), "{agent} should be a bot");
+            assert_eq!(info.device(), DeviceType::Bot);
+        }
+    }
+
+    #[test]
+    fn keeps_devices_named_like_bots() {
+        for agent in [
+            "Mozilla/5.0 (Linux; Android 10; CUBOT_NOTE_20) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+            "Mozilla/5.0 (Linux; Android 12; CUBOT X50) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36",
+        ] {
+            let info = UserAgentInfo::parse(agent);
+            assert!(!info.is_bot(), "{agent} should not be a bot");
+            assert_eq!(info.device(), DeviceType::Mobile);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Identifies a visitor for unique counting, derived from the client address
+/// and user agent under the day's IP salt. Once the salt is deleted the key
+/// can no longer be recomputed from a known address, so queued and
+/// dead-lettered events do not pseudonymize visitors beyond that day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VisitorKey(String);
+
+impl VisitorKey {
+    pub fn new(ip_address: &str, user_agent: &str, salt: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(ip_address.trim().as_bytes());
+        hasher.update([0]);
+        hasher.update(user_agent.trim().as_bytes());
+
+        Self(hex::encode(&hasher.finalize()[..16]))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<VisitorKey> for String {
+    fn from(key: VisitorKey) -> Self {
+        key.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_address_and_agent_is_the_same_visitor() {
+        let visitor = VisitorKey::new("203.0.113.7", "Mozilla/5.0", b"salt");
+
+        assert_eq!(
+            visitor,
+            VisitorKey::new(" 203.0.113.7", "Mozilla/5.0 ", b"salt")
+        );
+        assert_ne!(
+            visitor,
+            VisitorKey::new("203.0.113.7", "curl/8.5.0", b"salt")
+        );
+        assert_ne!(
+            visitor,
+            VisitorKey::new("203.0.113.8", "Mozilla/5.0", b"salt")
+        );
+        assert_eq!(visitor.as_str().len(), 32);
+    }
+
+    #[test]
+    fn a_new_salt_is_a_new_visitor() {
+        assert_ne!(
+            VisitorKey::new("203.0.113.7", "Mozilla/5.0", b"monday"),
+            VisitorKey::new("203.0.113.7", "Mozilla/5.0", b"tuesday")
+        );
+    }
+
+    #[test]
+    fn fields_cannot_bleed_into_each_other() {
+        assert_ne!(
+            VisitorKey::new("1.2.3.4", "5", b"salt"),
+            VisitorKey::new("1.2.3.", "45", b"salt")
+        );
+    }
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    configuration::Configs,
+    features::urls::{dtos::UrlResponseDto, errors::UrlError, models::Url},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct BatchCreateQueryDto {
+    /// Fail the whole batch when any link cannot be created.
+    pub atomic: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum BatchItemResponseDto {
+    Success { index: usize, data: UrlResponseDto },
+    Fail { index: usize, error: String },
+}
+
+impl BatchItemResponseDto {
+    /// Converts the per-item results of a batch, reading the configured host only once.
+    pub fn from_results(results: Vec<Result<Url, UrlError>>) -> Vec<Self> {
+        let config = Configs::get().expect("Failed to read configuration");
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| match result {
+                Ok(url) => Self::Success {
+                    index,
+                    data: UrlResponseDto::from_url(url, &config.application.host),
+                },
+                Err(error) => Self::Fail {
+                    index,
+                    error: error.to_string(),
+                },
+            })
+            .collect()
+    }
+}
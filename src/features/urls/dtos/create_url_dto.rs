@@ -1,7 +1,10 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct CreateUrlDto {
     pub url: String,
     pub custom_code: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i32>,
 }
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::features::url_stats::value_objects::ExportFormat;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQueryDto {
+    pub format: Option<ExportFormat>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::features::urls::value_objects::{SortOrder, UrlSort};
+
+#[derive(Debug, Deserialize)]
+pub struct ListUrlsQueryDto {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    pub sort: Option<UrlSort>,
+    pub order: Option<SortOrder>,
+    /// Destination domain, subdomains included.
+    pub domain: Option<String>,
+    /// Case-insensitive substring of the destination URL.
+    pub q: Option<String>,
+    pub code_prefix: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
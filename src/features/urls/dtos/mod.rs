@@ -1,7 +1,19 @@
+mod batch_create_dto;
 mod create_url_dto;
+mod export_query_dto;
+mod list_urls_query_dto;
+mod log_query_dto;
+mod referrer_query_dto;
+mod timeseries_query_dto;
 mod update_url_dto;
 mod url_response;
 
+pub use batch_create_dto::*;
 pub use create_url_dto::*;
+pub use export_query_dto::*;
+pub use list_urls_query_dto::*;
+pub use log_query_dto::*;
+pub use referrer_query_dto::*;
+pub use timeseries_query_dto::*;
 pub use update_url_dto::*;
 pub use url_response::*;
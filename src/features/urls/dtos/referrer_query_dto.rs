@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct ReferrerQueryDto {
+    /// Number of referring domains to return, 10 by default and at most 100.
+    pub limit: Option<usize>,
+}
@@ -0,0 +1,12 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::features::url_stats::value_objects::TimeseriesInterval;
+
+#[derive(Debug, Deserialize)]
+pub struct TimeseriesQueryDto {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub interval: Option<TimeseriesInterval>,
+    pub tz: Option<String>,
+}
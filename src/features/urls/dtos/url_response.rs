@@ -2,7 +2,10 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{configuration::Configs, features::urls::models::Url};
+use crate::{
+    configuration::Configs,
+    features::urls::models::{ListedUrl, Url},
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UrlResponseDto {
@@ -10,22 +13,51 @@ pub struct UrlResponseDto {
     pub original_url: String,
     pub short_code: String,
     pub short_url: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_count: Option<i64>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-impl From<Url> for UrlResponseDto {
-    fn from(value: Url) -> Self {
-        let config = Configs::get().expect("Failed to read configuration");
-        let short_url = format!("{}/{}", config.application.host, value.short_code.as_str());
+impl UrlResponseDto {
+    pub fn from_url(value: Url, host: &str) -> Self {
+        let short_url = format!("{}/{}", host, value.short_code.as_str());
 
         Self {
             id: value.id.unwrap_or_else(Uuid::new_v4).to_string(),
+            expires_at: value.expiration.expires_at(),
+            max_clicks: value.expiration.max_clicks(),
+            access_count: None,
             original_url: value.original_url.into_inner(),
             short_code: value.short_code.into_inner(),
             short_url,
             created_at: value.created_at.unwrap_or_else(Utc::now),
-            updated_at: value.created_at.unwrap_or_else(Utc::now),
+            updated_at: value.updated_at.unwrap_or_else(Utc::now),
+            deleted_at: value.deleted_at,
         }
     }
+
+    /// Converts a page of listed links, reading the configured host only once.
+    pub fn from_listed(urls: Vec<ListedUrl>) -> Vec<Self> {
+        let config = Configs::get().expect("Failed to read configuration");
+
+        urls.into_iter()
+            .map(|listed| Self {
+                access_count: Some(listed.access_count),
+                ..Self::from_url(listed.url, &config.application.host)
+            })
+            .collect()
+    }
+}
+
+impl From<Url> for UrlResponseDto {
+    fn from(value: Url) -> Self {
+        let config = Configs::get().expect("Failed to read configuration");
+
+        Self::from_url(value, &config.application.host)
+    }
 }
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::features::urls::{entities::UrlEntity, errors::UrlError, models::ListedUrl};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ListedUrlEntity {
+    pub id: Uuid,
+    pub original_url: String,
+    pub short_code: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i32>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub revision: i32,
+    pub access_count: i64,
+}
+
+impl ListedUrlEntity {
+    pub fn to_domain(self) -> Result<ListedUrl, UrlError> {
+        let url = UrlEntity {
+            id: self.id,
+            original_url: self.original_url,
+            short_code: self.short_code,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            expires_at: self.expires_at,
+            max_clicks: self.max_clicks,
+            deleted_at: self.deleted_at,
+            revision: self.revision,
+        }
+        .to_domain()?;
+
+        Ok(ListedUrl {
+            url,
+            access_count: self.access_count,
+        })
+    }
+}
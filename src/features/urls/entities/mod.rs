@@ -1,3 +1,7 @@
+mod listed_url_entity;
 mod url_entity;
+mod url_revision_entity;
 
+pub use listed_url_entity::*;
 pub use url_entity::*;
+pub use url_revision_entity::*;
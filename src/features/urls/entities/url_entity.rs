@@ -5,7 +5,7 @@ use uuid::Uuid;
 use crate::features::urls::{
     errors::UrlError,
     models::Url,
-    value_objects::{ShortCode, ValidUrl},
+    value_objects::{Expiration, ShortCode, ValidUrl},
 };
 
 #[derive(Debug, Clone, FromRow)]
@@ -15,6 +15,10 @@ pub struct UrlEntity {
     pub short_code: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_clicks: Option<i32>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub revision: i32,
 }
 
 impl UrlEntity {
@@ -22,19 +26,26 @@ impl UrlEntity {
         Ok(Url {
             id: Some(self.id),
             original_url: ValidUrl::new(self.original_url.clone())?,
-            short_code: ShortCode::new(Some(self.short_code.clone()))?,
+            short_code: ShortCode::new(self.short_code.clone())?,
+            expiration: Expiration::from_parts(self.expires_at, self.max_clicks),
+            revision: Some(self.revision),
             created_at: Some(self.created_at),
             updated_at: Some(self.updated_at),
+            deleted_at: self.deleted_at,
         })
     }
 
     pub fn from_domain(domain_url: &Url) -> Self {
         Self {
-            id: domain_url.id.unwrap_or_else(|| Uuid::new_v4()),
+            id: domain_url.id.unwrap_or_else(Uuid::new_v4),
             original_url: String::from(domain_url.original_url.as_ref()),
             short_code: String::from(domain_url.short_code.as_str()),
             created_at: domain_url.created_at.unwrap_or_else(Utc::now),
             updated_at: domain_url.updated_at.unwrap_or_else(Utc::now),
+            expires_at: domain_url.expiration.expires_at(),
+            max_clicks: domain_url.expiration.max_clicks(),
+            deleted_at: domain_url.deleted_at,
+            revision: domain_url.revision.unwrap_or(1),
         }
     }
 }
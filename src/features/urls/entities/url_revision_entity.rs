@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::features::urls::models::{Actor, UrlRevision};
+
+#[derive(Debug, Clone, FromRow)]
+pub struct UrlRevisionEntity {
+    pub revision: i32,
+    pub original_url: String,
+    pub actor_key_id: Option<Uuid>,
+    pub actor: Option<String>,
+    pub rollback_of: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UrlRevisionEntity {
+    pub fn to_domain(self) -> UrlRevision {
+        UrlRevision {
+            revision: self.revision,
+            original_url: self.original_url,
+            actor: self
+                .actor_key_id
+                .zip(self.actor)
+                .map(|(key_id, name)| Actor { key_id, name }),
+            rollback_of: self.rollback_of,
+            created_at: self.created_at,
+        }
+    }
+}
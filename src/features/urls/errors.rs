@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-use crate::error::AppError;
+use crate::{error::AppError, features::urls::value_objects::ShortCode};
 
 #[derive(Debug, Error)]
 pub enum UrlError {
@@ -16,21 +16,54 @@ pub enum UrlError {
     #[error("URL not found: {0}")]
     NotFound(String),
 
+    #[error("Invalid expiration: {0}")]
+    InvalidExpiration(String),
+
+    #[error("URL is no longer available: {0}")]
+    Expired(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
     #[error("URL already exists: {0}")]
     Duplicate(String),
 
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+
+    #[error("Not created, another link in the batch failed")]
+    BatchAborted,
+
+    #[error("No short code available: {0}")]
+    ShortCodesExhausted(String),
+
     #[error("Database error")]
     Database(#[from] sqlx::Error),
 }
 
+impl UrlError {
+    pub fn code_in_use(short_code: &ShortCode) -> Self {
+        Self::Duplicate(format!(
+            "The short code '{}' is already in use",
+            short_code.as_str()
+        ))
+    }
+}
+
 impl From<UrlError> for AppError {
     fn from(error: UrlError) -> Self {
         match error {
             UrlError::InvalidUrl(msg)
             | UrlError::InvalidShortCode(msg)
-            | UrlError::Duplicate(msg) => AppError::Validation(msg),
+            | UrlError::InvalidExpiration(msg)
+            | UrlError::InvalidQuery(msg)
+            | UrlError::InvalidBatch(msg) => AppError::Validation(msg),
+            UrlError::Duplicate(msg) => AppError::Conflict(msg),
             UrlError::NotFound(msg) => AppError::NotFound(msg),
+            UrlError::Expired(msg) => AppError::Gone(msg),
+            UrlError::ShortCodesExhausted(msg) => AppError::Unavailable(msg),
             UrlError::UrlTooLong(len) => AppError::Validation(len.to_string()),
+            UrlError::BatchAborted => AppError::Validation(error.to_string()),
             UrlError::Database(error) => AppError::Database(error),
         }
     }
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use crate::features::urls::{
+    errors::UrlError, repository::ShortCodeCounterRepository, value_objects::ShortCode,
+};
+
+use super::{ShortCodeGenerator, check_length};
+
+const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// 62^10 is the largest code space that fits in a `u64`.
+const MAX_LENGTH: usize = 10;
+
+const ROUNDS: u64 = 4;
+
+/// Turns a shared counter into codes that never collide with one another and
+/// don't reveal how many links exist or which one comes next.
+pub struct CounterCodeGenerator {
+    counter: Arc<ShortCodeCounterRepository>,
+    permutation: FeistelPermutation,
+    length: usize,
+}
+
+impl CounterCodeGenerator {
+    pub fn new(
+        counter: Arc<ShortCodeCounterRepository>,
+        length: usize,
+        key: u64,
+    ) -> Result<Self, UrlError> {
+        check_length("short_codes.length", length)?;
+
+        if length > MAX_LENGTH {
+            return Err(UrlError::InvalidShortCode(format!(
+                "Counter codes can not be longer than {MAX_LENGTH} characters"
+            )));
+        }
+
+        Ok(Self {
+            counter,
+            permutation: FeistelPermutation::new(62u64.pow(length as u32), key),
+            length,
+        })
+    }
+
+    fn code(&self, value: u64) -> Result<ShortCode, UrlError> {
+        if value >= self.permutation.domain {
+            return Err(UrlError::ShortCodesExhausted(format!(
+                "Every counter code of {} characters is used, raise short_codes.length",
+                self.length
+            )));
+        }
+
+        ShortCode::new(encode_base62(self.permutation.permute(value), self.length))
+    }
+}
+
+impl ShortCodeGenerator for CounterCodeGenerator {
+    async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        let values = self.counter.next_values(count).await?;
+
+        values.into_iter().map(|value| self.code(value)).collect()
+    }
+}
+
+/// Left-pads with zeros so every code has the same length.
+fn encode_base62(mut value: u64, length: usize) -> String {
+    let mut code = vec![BASE62[0]; length];
+
+    for position in (0..length).rev() {
+        code[position] = BASE62[(value % 62) as usize];
+        value /= 62;
+    }
+
+    String::from_utf8(code).expect("base62 digits are ascii")
+}
+
+/// A keyed bijection of `0..domain` onto itself.
+///
+/// A balanced Feistel network permutes the smallest even number of bits
+/// covering the domain, and values landing outside it are encrypted again
+/// (cycle walking) until they fall back in.
+struct FeistelPermutation {
+    domain: u64,
+    half_bits: u32,
+    key: u64,
+}
+
+impl FeistelPermutation {
+    fn new(domain: u64, key: u64) -> Self {
+        let bits = u64::BITS - (domain - 1).leading_zeros();
+
+        Self {
+            domain,
+            half_bits: bits.div_ceil(2),
+            key,
+        }
+    }
+
+    fn permute(&self, value: u64) -> u64 {
+        let mut value = self.encrypt(value);
+
+        while value >= self.domain {
+            value = self.encrypt(value);
+        }
+
+        value
+    }
+
+    fn encrypt(&self, value: u64) -> u64 {
+        let mask = (1u64 << self.half_bits) - 1;
+        let (mut left, mut right) = (value >> self.half_bits, value & mask);
+
+        for round in 0..ROUNDS {
+            let mixed = left ^ (self.round(right, round) & mask);
+            left = right;
+            right = mixed;
+        }
+
+        (left << self.half_bits) | right
+    }
+
+    /// splitmix64 finalizer over the half block, the key and the round number.
+    fn round(&self, half: u64, round: u64) -> u64 {
+        let mut z = half
+            ^ self.key.rotate_left(round as u32 * 16)
+            ^ round.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{FeistelPermutation, encode_base62};
+
+    #[test]
+    fn permute_the_domain_onto_itself() {
+        let domain = 62u64.pow(3);
+        let permutation = FeistelPermutation::new(domain, 42);
+
+        let permuted: HashSet<u64> = (0..domain)
+            .map(|value| permutation.permute(value))
+            .collect();
+
+        assert_eq!(permuted.len() as u64, domain);
+        assert!(permuted.iter().all(|value| *value < domain));
+    }
+
+    #[test]
+    fn scatter_consecutive_values() {
+        let permutation = FeistelPermutation::new(62u64.pow(8), 7);
+
+        let first = permutation.permute(1);
+        let second = permutation.permute(2);
+
+        assert_ne!(first.abs_diff(second), 1);
+    }
+
+    #[test]
+    fn depend_on_the_key() {
+        let domain = 62u64.pow(8);
+
+        let with_one = FeistelPermutation::new(domain, 1).permute(1000);
+        let with_other = FeistelPermutation::new(domain, 2).permute(1000);
+
+        assert_ne!(with_one, with_other);
+    }
+
+    #[test]
+    fn encode_base62_with_padding() {
+        assert_eq!(encode_base62(0, 4), "0000");
+        assert_eq!(encode_base62(61, 4), "000z");
+        assert_eq!(encode_base62(62, 4), "0010");
+        assert_eq!(encode_base62(62u64.pow(10) - 1, 10), "zzzzzzzzzz");
+    }
+}
@@ -0,0 +1,11 @@
+mod counter_code_generator;
+mod pronounceable_code_generator;
+mod random_code_generator;
+mod short_code_generator;
+mod short_code_pool;
+
+pub use counter_code_generator::*;
+pub use pronounceable_code_generator::*;
+pub use random_code_generator::*;
+pub use short_code_generator::*;
+pub use short_code_pool::*;
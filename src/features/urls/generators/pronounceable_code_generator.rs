@@ -0,0 +1,81 @@
+use rand::Rng;
+
+use crate::features::urls::{errors::UrlError, value_objects::ShortCode};
+
+use super::{ShortCodeGenerator, check_length};
+
+/// Consonants that read unambiguously, leaving out `c`, `q`, `x`, `w`, `y`
+/// and `l`, which is easily mistaken for `1`.
+const CONSONANTS: &[u8] = b"bdfghjkmnprstvz";
+const VOWELS: &[u8] = b"aeiou";
+
+/// Each syllable only multiplies the code space by 75, so fewer than five
+/// (75^5, about 2.4 billion codes) would quickly run into collisions.
+const MIN_SYLLABLES: usize = 5;
+
+/// Builds made-up words such as `kodavimesu` that are easy to read out loud.
+pub struct PronounceableCodeGenerator {
+    syllables: usize,
+}
+
+impl PronounceableCodeGenerator {
+    pub fn new(syllables: usize) -> Result<Self, UrlError> {
+        if syllables < MIN_SYLLABLES {
+            return Err(UrlError::InvalidShortCode(format!(
+                "short_codes.syllables must be at least {MIN_SYLLABLES}, got {syllables}"
+            )));
+        }
+        check_length("short_codes.syllables", syllables * 2)?;
+
+        Ok(Self { syllables })
+    }
+
+    fn code(&self, rng: &mut impl Rng) -> String {
+        let mut code = String::with_capacity(self.syllables * 2);
+
+        for _ in 0..self.syllables {
+            code.push(CONSONANTS[rng.random_range(0..CONSONANTS.len())] as char);
+            code.push(VOWELS[rng.random_range(0..VOWELS.len())] as char);
+        }
+
+        code
+    }
+}
+
+impl ShortCodeGenerator for PronounceableCodeGenerator {
+    async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        let mut rng = rand::rng();
+
+        (0..count)
+            .map(|_| ShortCode::new(self.code(&mut rng)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CONSONANTS, PronounceableCodeGenerator, VOWELS};
+
+    #[test]
+    fn alternate_consonants_and_vowels() {
+        let generator = PronounceableCodeGenerator::new(5).unwrap();
+
+        let code = generator.code(&mut rand::rng());
+
+        assert_eq!(code.len(), 10);
+        for (position, c) in code.bytes().enumerate() {
+            let letters = if position % 2 == 0 {
+                CONSONANTS
+            } else {
+                VOWELS
+            };
+            assert!(letters.contains(&c));
+        }
+    }
+
+    #[test]
+    fn reject_codes_out_of_bounds() {
+        assert!(PronounceableCodeGenerator::new(4).is_err());
+        assert!(PronounceableCodeGenerator::new(9).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use rand::Rng;
+
+use crate::features::urls::{errors::UrlError, value_objects::ShortCode};
+
+use super::{ShortCodeGenerator, check_length};
+
+/// Characters commonly misread as one another when a code is typed back.
+const LOOKALIKES: &str = "0Oo1lI";
+
+/// Draws every character of a code at random from an alphabet.
+pub struct RandomCodeGenerator {
+    alphabet: Vec<char>,
+    length: usize,
+}
+
+impl RandomCodeGenerator {
+    pub fn new(alphabet: &str, length: usize, exclude_lookalikes: bool) -> Result<Self, UrlError> {
+        check_length("short_codes.length", length)?;
+
+        if let Some(invalid) = alphabet
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+        {
+            return Err(UrlError::InvalidShortCode(format!(
+                "short_codes.alphabet can not contain '{invalid}', only letters, digits, '-' and '_'"
+            )));
+        }
+
+        let mut characters: Vec<char> = Vec::with_capacity(alphabet.len());
+
+        for c in alphabet.chars() {
+            let excluded = exclude_lookalikes && LOOKALIKES.contains(c);
+
+            if !excluded && !characters.contains(&c) {
+                characters.push(c);
+            }
+        }
+
+        if characters.len() < 2 {
+            return Err(UrlError::InvalidShortCode(String::from(
+                "short_codes.alphabet needs at least two distinct usable characters",
+            )));
+        }
+
+        Ok(Self {
+            alphabet: characters,
+            length,
+        })
+    }
+
+    fn code(&self, rng: &mut impl Rng) -> String {
+        (0..self.length)
+            .map(|_| self.alphabet[rng.random_range(0..self.alphabet.len())])
+            .collect()
+    }
+}
+
+impl ShortCodeGenerator for RandomCodeGenerator {
+    async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        let mut rng = rand::rng();
+
+        (0..count)
+            .map(|_| ShortCode::new(self.code(&mut rng)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RandomCodeGenerator;
+
+    const BASE62: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    #[test]
+    fn generate_codes_of_the_configured_length_from_the_alphabet() {
+        let generator = RandomCodeGenerator::new(BASE62, 10, false).unwrap();
+        let mut rng = rand::rng();
+
+        for _ in 0..200 {
+            let code = generator.code(&mut rng);
+            assert_eq!(code.len(), 10);
+            assert!(code.chars().all(|c| c.is_ascii_alphanumeric()));
+        }
+    }
+
+    #[test]
+    fn leave_out_lookalike_characters() {
+        let generator = RandomCodeGenerator::new(BASE62, 16, true).unwrap();
+        let mut rng = rand::rng();
+
+        for _ in 0..200 {
+            assert!(
+                !generator
+                    .code(&mut rng)
+                    .contains(['0', 'O', 'o', '1', 'l', 'I'])
+            );
+        }
+    }
+
+    #[test]
+    fn reject_invalid_settings() {
+        assert!(RandomCodeGenerator::new(BASE62, 2, false).is_err());
+        assert!(RandomCodeGenerator::new(BASE62, 17, false).is_err());
+        assert!(RandomCodeGenerator::new("abc/", 8, false).is_err());
+        assert!(RandomCodeGenerator::new("0O1", 8, true).is_err());
+    }
+}
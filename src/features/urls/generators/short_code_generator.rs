@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use crate::{
+    configuration::{ShortCodeSettings, ShortCodeStrategy},
+    features::urls::{
+        errors::UrlError, repository::ShortCodeCounterRepository, value_objects::ShortCode,
+    },
+};
+
+use super::{CounterCodeGenerator, PronounceableCodeGenerator, RandomCodeGenerator, ShortCodePool};
+
+pub trait ShortCodeGenerator: Send + Sync {
+    /// Generates `count` new codes. Only the counter guarantees they were never
+    /// handed out before, callers still have to handle taken codes.
+    fn generate(
+        &self,
+        count: usize,
+    ) -> impl Future<Output = Result<Vec<ShortCode>, UrlError>> + Send;
+}
+
+enum Strategy {
+    Random(RandomCodeGenerator),
+    Counter(CounterCodeGenerator),
+    Pronounceable(PronounceableCodeGenerator),
+}
+
+impl ShortCodeGenerator for Strategy {
+    async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        match self {
+            Self::Random(generator) => generator.generate(count).await,
+            Self::Counter(generator) => generator.generate(count).await,
+            Self::Pronounceable(generator) => generator.generate(count).await,
+        }
+    }
+}
+
+/// The generator selected by `short_codes.strategy`, drawing from the
+/// pre-generated pool first when there is one.
+pub struct CodeGenerator {
+    strategy: Strategy,
+    max_attempts: usize,
+    pool: Option<ShortCodePool>,
+}
+
+impl CodeGenerator {
+    pub fn new(
+        settings: &ShortCodeSettings,
+        counter: Arc<ShortCodeCounterRepository>,
+        pool: Option<ShortCodePool>,
+    ) -> Result<Self, UrlError> {
+        let strategy = match settings.strategy {
+            ShortCodeStrategy::Random => Strategy::Random(RandomCodeGenerator::new(
+                &settings.alphabet,
+                settings.length,
+                settings.exclude_lookalikes,
+            )?),
+            ShortCodeStrategy::Counter => Strategy::Counter(CounterCodeGenerator::new(
+                counter,
+                settings.length,
+                settings.counter_key,
+            )?),
+            ShortCodeStrategy::Pronounceable => {
+                Strategy::Pronounceable(PronounceableCodeGenerator::new(settings.syllables)?)
+            }
+        };
+
+        Ok(Self {
+            strategy,
+            max_attempts: settings.max_attempts.max(1),
+            pool,
+        })
+    }
+
+    /// Rounds of regenerating codes that turn out to be taken before giving up.
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    pub fn pool(&self) -> Option<&ShortCodePool> {
+        self.pool.as_ref()
+    }
+
+    /// Tops the pool up with codes from the strategy, returns how many were added.
+    pub async fn refill_pool(&self) -> Result<u64, UrlError> {
+        match &self.pool {
+            Some(pool) => pool.refill(&self.strategy).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Generates a code on the spot, single creates claim pooled codes through
+    /// the insert itself.
+    pub async fn generate_one(&self) -> Result<ShortCode, UrlError> {
+        self.strategy.generate(1).await?.pop().ok_or_else(|| {
+            UrlError::ShortCodesExhausted(String::from("The generator returned no code"))
+        })
+    }
+}
+
+impl ShortCodeGenerator for CodeGenerator {
+    async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        let mut short_codes = match &self.pool {
+            Some(pool) => pool.claim(count).await?,
+            None => Vec::new(),
+        };
+
+        if short_codes.len() < count {
+            let missing = count - short_codes.len();
+            short_codes.extend(self.strategy.generate(missing).await?);
+        }
+
+        Ok(short_codes)
+    }
+}
+
+/// Rejects settings producing codes that `ShortCode` would not accept back.
+pub(super) fn check_length(name: &str, length: usize) -> Result<(), UrlError> {
+    let (min, max) = (ShortCode::MIN_LENGTH, ShortCode::MAX_LENGTH);
+
+    if length < min || length > max {
+        return Err(UrlError::InvalidShortCode(format!(
+            "{name} must produce codes of {min} to {max} characters, got {length}"
+        )));
+    }
+
+    Ok(())
+}
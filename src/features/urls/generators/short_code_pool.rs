@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use crate::{
+    configuration::ShortCodePoolSettings,
+    features::urls::{
+        errors::UrlError,
+        repository::{IShortCodePoolRepository, ShortCodePoolRepository},
+        value_objects::ShortCode,
+    },
+    infrastructure::telemetry::ApplicationMetrics,
+};
+
+use super::ShortCodeGenerator;
+
+/// Codes generated ahead of time and checked against existing links, so
+/// creates neither generate codes nor retry on collisions.
+pub struct ShortCodePool<R = ShortCodePoolRepository> {
+    repository: Arc<R>,
+    metrics: Arc<ApplicationMetrics>,
+    settings: ShortCodePoolSettings,
+}
+
+impl<R: IShortCodePoolRepository> ShortCodePool<R> {
+    pub fn new(
+        repository: Arc<R>,
+        metrics: Arc<ApplicationMetrics>,
+        settings: ShortCodePoolSettings,
+    ) -> Self {
+        Self {
+            repository,
+            metrics,
+            settings,
+        }
+    }
+
+    /// Claims up to `count` codes, fewer when the pool runs dry.
+    pub async fn claim(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+        let codes = self.repository.claim(count).await?;
+        self.record_claims(count, codes.len());
+
+        codes.into_iter().map(ShortCode::new).collect()
+    }
+
+    /// Counts `claimed` of `requested` codes as taken from the pool, the rest
+    /// as misses. The depth gauge follows claims and releases until the next
+    /// refill sets it.
+    pub fn record_claims(&self, requested: usize, claimed: usize) {
+        self.metrics
+            .increment_short_code_pool_claimed_by(claimed as u64);
+        self.metrics
+            .increment_short_code_pool_missed_by(requested.saturating_sub(claimed) as u64);
+        self.metrics
+            .decrement_short_code_pool_depth_by(claimed as i64);
+    }
+
+    /// Puts back codes claimed for links that were not created, leaving out
+    /// those taken in the meantime. Returns how many were added.
+    pub async fn release(&self, short_codes: &[String]) -> Result<u64, UrlError> {
+        let added = self.repository.add(short_codes).await?;
+        self.metrics
+            .increment_short_code_pool_depth_by(added as i64);
+
+        Ok(added)
+    }
+
+    /// Fills the pool back up to its size with codes from `generator`,
+    /// returns how many were added.
+    pub async fn refill(&self, generator: &impl ShortCodeGenerator) -> Result<u64, UrlError> {
+        let mut depth = self.repository.depth().await?.max(0) as usize;
+        let mut refilled = 0;
+
+        while depth < self.settings.size {
+            let count = (self.settings.size - depth).min(self.settings.refill_batch_size.max(1));
+            let short_codes: Vec<String> = generator
+                .generate(count)
+                .await?
+                .into_iter()
+                .map(ShortCode::into_inner)
+                .collect();
+
+            let added = self.repository.add(&short_codes).await?;
+            self.metrics.increment_short_code_pool_refilled_by(added);
+
+            // Every code was taken, leave it to the next round rather than spin
+            if added == 0 {
+                break;
+            }
+
+            depth += added as usize;
+            refilled += added;
+        }
+
+        self.metrics.set_short_code_pool_depth(depth as i64);
+
+        Ok(refilled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use super::*;
+
+    /// Keeps pooled codes in memory, refusing the ones in `taken`.
+    #[derive(Default)]
+    struct MemoryPool {
+        codes: Mutex<Vec<String>>,
+        taken: HashSet<String>,
+        adds: AtomicUsize,
+    }
+
+    impl IShortCodePoolRepository for MemoryPool {
+        async fn claim(&self, count: usize) -> Result<Vec<String>, UrlError> {
+            let mut codes = self.codes.lock().unwrap();
+            let count = count.min(codes.len());
+
+            Ok(codes.drain(..count).collect())
+        }
+
+        async fn add(&self, short_codes: &[String]) -> Result<u64, UrlError> {
+            self.adds.fetch_add(1, Ordering::Relaxed);
+            let mut codes = self.codes.lock().unwrap();
+            let mut added = 0;
+
+            for code in short_codes {
+                if !self.taken.contains(code) && !codes.contains(code) {
+                    codes.push(code.clone());
+                    added += 1;
+                }
+            }
+
+            Ok(added)
+        }
+
+        async fn depth(&self) -> Result<i64, UrlError> {
+            Ok(self.codes.lock().unwrap().len() as i64)
+        }
+    }
+
+    /// Hands out `pool0000`, `pool0001`, ... and starts over after `cycle` codes.
+    struct SequenceGenerator {
+        next: AtomicUsize,
+        cycle: usize,
+    }
+
+    impl SequenceGenerator {
+        fn new(cycle: usize) -> Self {
+            Self {
+                next: AtomicUsize::new(0),
+                cycle,
+            }
+        }
+    }
+
+    impl ShortCodeGenerator for SequenceGenerator {
+        async fn generate(&self, count: usize) -> Result<Vec<ShortCode>, UrlError> {
+            (0..count)
+                .map(|_| {
+                    let index = self.next.fetch_add(1, Ordering::Relaxed) % self.cycle;
+                    ShortCode::new(format!("pool{index:04}"))
+                })
+                .collect()
+        }
+    }
+
+    fn pool(
+        repository: MemoryPool,
+        size: usize,
+    ) -> (ShortCodePool<MemoryPool>, Arc<ApplicationMetrics>) {
+        let metrics = Arc::new(ApplicationMetrics::new());
+        let settings = ShortCodePoolSettings {
+            enabled: true,
+            size,
+            refill_batch_size: 4,
+            refill_interval_secs: 60,
+        };
+
+        (
+            ShortCodePool::new(Arc::new(repository), metrics.clone(), settings),
+            metrics,
+        )
+    }
+
+    fn metric(metrics: &ApplicationMetrics, name: &str) -> i64 {
+        metrics
+            .export_metrics()
+            .unwrap()
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')?.parse().ok())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn counts_claims_and_misses_and_follows_them_in_the_depth() {
+        let repository = MemoryPool::default();
+        repository
+            .add(&["abc1".into(), "abc2".into(), "abc3".into()])
+            .await
+            .unwrap();
+        let (pool, metrics) = pool(repository, 3);
+        metrics.set_short_code_pool_depth(3);
+
+        let claimed = pool.claim(5).await.unwrap();
+
+        assert_eq!(claimed.len(), 3);
+        assert_eq!(metric(&metrics, "short_code_pool_claimed_total"), 3);
+        assert_eq!(metric(&metrics, "short_code_pool_missed_total"), 2);
+        assert_eq!(metric(&metrics, "short_code_pool_depth"), 0);
+    }
+
+    #[tokio::test]
+    async fn released_codes_can_be_claimed_again() {
+        let repository = MemoryPool {
+            taken: HashSet::from(["abc2".to_string()]),
+            ..MemoryPool::default()
+        };
+        repository
+            .add(&["abc1".into(), "abc3".into()])
+            .await
+            .unwrap();
+        let (pool, metrics) = pool(repository, 2);
+        metrics.set_short_code_pool_depth(2);
+
+        pool.claim(2).await.unwrap();
+        let released = pool.release(&["abc1".into(), "abc2".into()]).await.unwrap();
+
+        assert_eq!(released, 1);
+        assert_eq!(metric(&metrics, "short_code_pool_depth"), 1);
+        let claimed = pool.claim(2).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].as_str(), "abc1");
+    }
+
+    #[tokio::test]
+    async fn refills_up_to_the_size_in_batches() {
+        let (pool, metrics) = pool(MemoryPool::default(), 10);
+
+        assert_eq!(pool.refill(&SequenceGenerator::new(100)).await.unwrap(), 10);
+        assert_eq!(pool.repository.adds.load(Ordering::Relaxed), 3);
+        assert_eq!(metric(&metrics, "short_code_pool_depth"), 10);
+
+        pool.claim(1).await.unwrap();
+        assert_eq!(pool.refill(&SequenceGenerator::new(100)).await.unwrap(), 1);
+        assert_eq!(pool.repository.depth().await.unwrap(), 10);
+    }
+
+    #[tokio::test]
+    async fn stops_refilling_when_every_code_is_taken() {
+        let repository = MemoryPool {
+            taken: (0..4).map(|index| format!("pool{index:04}")).collect(),
+            ..MemoryPool::default()
+        };
+        let (pool, metrics) = pool(repository, 10);
+
+        assert_eq!(pool.refill(&SequenceGenerator::new(4)).await.unwrap(), 0);
+        assert_eq!(pool.repository.adds.load(Ordering::Relaxed), 1);
+        assert_eq!(metric(&metrics, "short_code_pool_depth"), 0);
+    }
+}
@@ -0,0 +1,11 @@
+use actix_web::{HttpResponse, web::Data};
+
+use crate::{error::AppError, infrastructure::telemetry::ApplicationMetrics};
+
+pub async fn metrics(metrics: Data<ApplicationMetrics>) -> Result<HttpResponse, AppError> {
+    let body = metrics.export_metrics()?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body))
+}
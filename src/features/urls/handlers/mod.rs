@@ -1,5 +1,7 @@
 mod healthz_handler;
+mod metrics_handler;
 mod url_handler;
 
 pub use healthz_handler::*;
+pub use metrics_handler::*;
 pub use url_handler::*;
@@ -1,44 +1,68 @@
-use std::{borrow::Cow, time::Instant};
+use std::{borrow::Cow, convert::Infallible};
 
 use actix_web::{
     HttpRequest, HttpResponse,
-    web::{Data, Json, Path},
+    web::{Bytes, Data, Json, Path, Query},
 };
-use serde_json::Value;
+use futures_util::TryStreamExt;
+use serde::Serialize;
+use serde_json::{Value, json};
 
-use actix_web::http::header;
+use actix_web::http::{StatusCode, header};
 
 use crate::{
     error::AppError,
     features::{
-        url_stats::{queue::StatsEvent, service::IUrlStatsService},
+        api_keys::extractors::{Authorized, LinksDelete, LinksWrite, StatsRead},
+        url_stats::{
+            export::ExportStream,
+            live::LiveEvent,
+            queue::StatsEvent,
+            service::IUrlStatsService,
+            value_objects::{ExportFormat, ExportQuery, LogQuery, Referrer, TimeseriesRange},
+        },
         urls::{
-            dtos::{CreateUrlDto, UpdateUrlDto},
+            dtos::{
+                BatchCreateQueryDto, BatchItemResponseDto, CreateUrlDto, ExportQueryDto,
+                ListUrlsQueryDto, LogQueryDto, ReferrerQueryDto, TimeseriesQueryDto, UpdateUrlDto,
+                UrlResponseDto,
+            },
+            models::{Actor, NewUrl},
             service::IUrlService,
-            value_objects::{ShortCode, ValidUrl},
+            value_objects::{Expiration, ExpirationUpdate, ShortCode, UrlListQuery, ValidUrl},
         },
     },
-    infrastructure::server::{ApiResponse, AppServices, QueueProcessor},
+    infrastructure::{
+        securtity::ClientIpResolver,
+        server::{ApiResponse, AppServices, PageMeta, QueueProcessor},
+    },
 };
 
 pub struct UrlHandler;
 
 impl UrlHandler {
     pub async fn create_short_url(
+        auth: Authorized<LinksWrite>,
         payload: Json<CreateUrlDto>,
         service: Data<AppServices>,
     ) -> Result<HttpResponse, AppError> {
-        let valid_url = ValidUrl::new(payload.0.url)?;
+        let actor = Actor::from(&auth.api_key);
+        let payload = payload.into_inner();
+        let valid_url = ValidUrl::new(payload.url)?;
+        let expiration = Expiration::new(payload.expires_at, payload.max_clicks)?;
 
-        let url = if let Some(custom_code) = payload.0.custom_code {
-            let short_code = ShortCode::new(Some(custom_code))?;
+        let url = if let Some(custom_code) = payload.custom_code {
+            let short_code = ShortCode::new(custom_code)?;
 
             service
                 .url_service
-                .create_short_url(valid_url, Some(short_code))
+                .create_short_url(valid_url, Some(short_code), expiration, actor)
                 .await
         } else {
-            service.url_service.create_short_url(valid_url, None).await
+            service
+                .url_service
+                .create_short_url(valid_url, None, expiration, actor)
+                .await
         };
 
         match url {
@@ -47,6 +71,96 @@ impl UrlHandler {
         }
     }
 
+    /// Creates up to `MAX_BATCH_SIZE` links, reporting success or failure per item
+    /// in request order. With `atomic=true` nothing is created when any item fails.
+    pub async fn create_short_urls(
+        auth: Authorized<LinksWrite>,
+        query: Query<BatchCreateQueryDto>,
+        payload: Json<Vec<CreateUrlDto>>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let atomic = query.atomic.unwrap_or(false);
+        let items = payload
+            .into_inner()
+            .into_iter()
+            .map(|item| {
+                Ok(NewUrl {
+                    original_url: ValidUrl::new(item.url)?,
+                    short_code: item.custom_code.map(ShortCode::new).transpose()?,
+                    expiration: Expiration::new(item.expires_at, item.max_clicks)?,
+                })
+            })
+            .collect();
+
+        let results = service
+            .url_service
+            .create_short_urls(items, atomic, Actor::from(&auth.api_key))
+            .await?;
+        let failed = results.iter().any(Result::is_err);
+        let items = BatchItemResponseDto::from_results(results);
+
+        if atomic && failed {
+            return Ok(ApiResponse::<Value>::fail(
+                json!({ "items": items }),
+                StatusCode::BAD_REQUEST,
+            ));
+        }
+
+        Ok(ApiResponse::success(items))
+    }
+
+    /// Lists links newest first, or by click count, with optional filters.
+    pub async fn list_urls(
+        _auth: Authorized<StatsRead>,
+        query: Query<ListUrlsQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let query = Self::list_query(query.into_inner())?;
+
+        Self::list_response(query, &service).await
+    }
+
+    /// Lists deleted links not purged yet, with the same filters as `list_urls`.
+    pub async fn list_trash(
+        _auth: Authorized<StatsRead>,
+        query: Query<ListUrlsQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let query = Self::list_query(query.into_inner())?.in_trash();
+
+        Self::list_response(query, &service).await
+    }
+
+    fn list_query(query: ListUrlsQueryDto) -> Result<UrlListQuery, AppError> {
+        Ok(UrlListQuery::new(
+            query.limit,
+            query.cursor,
+            query.sort,
+            query.order,
+            query.domain,
+            query.q,
+            query.code_prefix,
+            query.from,
+            query.to,
+        )?)
+    }
+
+    async fn list_response(
+        query: UrlListQuery,
+        service: &AppServices,
+    ) -> Result<HttpResponse, AppError> {
+        let limit = query.limit();
+        let page = service.url_service.list_urls(query).await?;
+
+        Ok(ApiResponse::success_with_meta(
+            UrlResponseDto::from_listed(page.urls),
+            PageMeta {
+                limit,
+                next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+            },
+        ))
+    }
+
     /// Retrieves the original URL associated with a short code.
     ///
     /// # Arguments
@@ -54,38 +168,59 @@ impl UrlHandler {
     /// * `service` - Application services container
     ///
     /// # Returns
-    /// Redirect to the original short code URL
+    /// Redirect to the original short code URL, or `410 Gone` once the link
+    /// has expired or used up its click quota
     pub async fn retreive_url_by_short_code(
         param: Path<String>,
         service: Data<AppServices>,
         queue: Data<QueueProcessor>,
         req: HttpRequest,
     ) -> Result<HttpResponse, AppError> {
-        let connection_info = req.connection_info();
-        let ip = connection_info.realip_remote_addr().unwrap_or("unknown");
+        let ip = ClientIpResolver::client_ip(&req)
+            .map_or_else(|| String::from("unknown"), |address| address.to_string());
         let user_agent = UrlHandler::user_agent(&req);
+        let referrer = req
+            .headers()
+            .get(header::REFERER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Referrer::parse);
 
-        let short_code = ShortCode::new(Some(param.into_inner()))?;
+        let short_code = ShortCode::new(param.into_inner())?;
 
         let result = service
             .url_service
             .get_url_by_short_code(short_code)
             .await?;
 
-        let event = StatsEvent {
-            url: result.clone(),
-            ip_address: ip.to_string(),
-            user_agent: user_agent,
-            timestamp: Instant::now(),
-        };
+        service.metrics.increment_url_access();
 
-        if let Err(error) = queue.stats_processor.sender.try_send(event) {
-            println!("Stats channel full: {}", error) // replace this with trace
+        match StatsEvent::new(
+            &result,
+            ip,
+            user_agent,
+            referrer,
+            UrlHandler::do_not_track(&req),
+        ) {
+            Ok(event) => queue.stats_processor.publish(event),
+            Err(error) => tracing::warn!(
+                "Skipping stats for {}: {}",
+                result.short_code.as_str(),
+                error
+            ),
         }
 
         Ok(ApiResponse::<&str>::redirect(result.original_url.as_str()))
     }
 
+    /// Whether the visitor opted out of tracking with `DNT` or `Sec-GPC`.
+    fn do_not_track(req: &HttpRequest) -> bool {
+        ["dnt", "sec-gpc"].iter().any(|name| {
+            req.headers()
+                .get(*name)
+                .is_some_and(|value| value.as_bytes().trim_ascii() == b"1")
+        })
+    }
+
     fn user_agent(req: &HttpRequest) -> String {
         req.headers()
             .get(header::USER_AGENT)
@@ -99,46 +234,288 @@ impl UrlHandler {
     }
 
     pub async fn fetch_short_code_stats(
+        _auth: Authorized<StatsRead>,
         param: Path<String>,
         service: Data<AppServices>,
     ) -> Result<HttpResponse, AppError> {
-        let short_code = ShortCode::new(Some(param.into_inner()))?;
+        let short_code = ShortCode::new(param.into_inner())?;
 
-        let response = service
+        match service.url_stats_service.fetch_stats(short_code).await? {
+            Some(report) => Ok(ApiResponse::success(report)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    pub async fn fetch_short_code_logs(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        query: Query<LogQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+        let query = query.into_inner();
+        let query = LogQuery::new(
+            query.limit,
+            query.cursor,
+            query.from,
+            query.to,
+            query.ip,
+            query.user_agent,
+            query.bot,
+        )?;
+        let limit = query.limit();
+
+        match service
+            .url_stats_service
+            .fetch_logs(short_code, query)
+            .await?
+        {
+            Some(page) => Ok(ApiResponse::success_with_meta(
+                page.logs,
+                PageMeta {
+                    limit,
+                    next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+                },
+            )),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    pub async fn fetch_short_code_referrers(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        query: Query<ReferrerQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+        let top = query.limit.unwrap_or(10).clamp(1, 100);
+
+        match service
+            .url_stats_service
+            .fetch_referrers(short_code, top)
+            .await?
+        {
+            Some(report) => Ok(ApiResponse::success(report)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    pub async fn fetch_short_code_clients(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+
+        match service.url_stats_service.fetch_clients(short_code).await? {
+            Some(report) => Ok(ApiResponse::success(report)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    pub async fn fetch_short_code_countries(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+
+        match service
+            .url_stats_service
+            .fetch_countries(short_code)
+            .await?
+        {
+            Some(countries) => Ok(ApiResponse::success(countries)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    pub async fn fetch_short_code_timeseries(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        query: Query<TimeseriesQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+        let query = query.into_inner();
+        let range = TimeseriesRange::new(query.from, query.to, query.interval, query.tz)?;
+
+        match service
+            .url_stats_service
+            .fetch_timeseries(short_code, range)
+            .await?
+        {
+            Some(timeseries) => Ok(ApiResponse::success(timeseries)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    /// Streams the click logs of a short code as a CSV, NDJSON or Parquet download.
+    pub async fn export_short_code_logs(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        query: Query<ExportQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+        let query = query.into_inner();
+        let query = ExportQuery::new(query.format, query.from, query.to)?;
+        let format = query.format();
+        let file_name = query.file_name(Some(short_code.as_str()));
+
+        match service
+            .url_stats_service
+            .export_logs(short_code, query)
+            .await?
+        {
+            Some(stream) => Ok(Self::export_response(format, file_name, stream)),
+            None => Err(AppError::NotFound("Short code not found".into())),
+        }
+    }
+
+    /// Streams the click logs of every link in a single download.
+    pub async fn export_logs(
+        _auth: Authorized<StatsRead>,
+        query: Query<ExportQueryDto>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let query = query.into_inner();
+        let query = ExportQuery::new(query.format, query.from, query.to)?;
+        let format = query.format();
+        let file_name = query.file_name(None);
+
+        let stream = service.url_stats_service.export_all_logs(query);
+
+        Ok(Self::export_response(format, file_name, stream))
+    }
+
+    fn export_response(
+        format: ExportFormat,
+        file_name: String,
+        stream: ExportStream,
+    ) -> HttpResponse {
+        // Headers are already sent once streaming starts, so a failure can
+        // only abort the transfer
+        let body = stream
+            .map_ok(Bytes::from)
+            .inspect_err(|error| tracing::error!("Click log export aborted: {}", error));
+
+        HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(header::ContentDisposition::attachment(file_name))
+            .streaming(body)
+    }
+
+    /// Streams clicks on a short code as Server-Sent Events as they are recorded.
+    /// Subscribers falling behind get a `lagged` event with the number of skipped clicks.
+    /// Only clicks recorded by this instance are streamed.
+    pub async fn stream_short_code_clicks(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+
+        let url_id = service
             .url_stats_service
-            .fetch_stats(short_code)
-            .await
-            .map_err(|error| AppError::NotFound(error.to_string()));
+            .find_url_id(&short_code)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Short code not found".into()))?;
 
-        let response = match response {
-            Ok(value) => Ok(ApiResponse::success(value)),
-            Err(error) => Err(error),
+        let mut subscription = service.live_clicks.subscribe(url_id).ok_or_else(|| {
+            AppError::Unavailable("Too many live connections, try again later".into())
+        })?;
+        let mut keep_alive = tokio::time::interval(service.live_clicks.keep_alive());
+
+        let body = async_stream::stream! {
+            loop {
+                tokio::select! {
+                    event = subscription.next() => match event {
+                        Some(LiveEvent::Click(click)) => {
+                            yield Ok::<_, Infallible>(Self::sse_event("click", &*click))
+                        }
+                        Some(LiveEvent::Lagged(skipped)) => {
+                            yield Ok(Self::sse_event("lagged", &json!({ "skipped": skipped })))
+                        }
+                        None => break,
+                    },
+                    _ = keep_alive.tick() => yield Ok(Bytes::from_static(b": keep-alive\n\n")),
+                }
+            }
         };
 
-        response
+        Ok(HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .insert_header(("X-Accel-Buffering", "no"))
+            .streaming(body))
+    }
+
+    fn sse_event(event: &str, data: &impl Serialize) -> Bytes {
+        let data = serde_json::to_string(data).unwrap_or_default();
+
+        Bytes::from(format!("event: {event}\ndata: {data}\n\n"))
     }
 
     pub async fn update_url_by_short_code(
+        auth: Authorized<LinksWrite>,
         param: Path<String>,
         payload: Json<UpdateUrlDto>,
         service: Data<AppServices>,
     ) -> Result<HttpResponse, AppError> {
-        let short_code = ShortCode::new(Some(param.into_inner()))?;
-        let valid_url = ValidUrl::new(payload.url.clone())?;
+        let payload = payload.into_inner();
+        let short_code = ShortCode::new(param.into_inner())?;
+        let valid_url = payload.url.map(ValidUrl::new).transpose()?;
+        let expiration = ExpirationUpdate::new(payload.expires_at, payload.max_clicks)?;
 
         let response = service
             .url_service
-            .update_url_by_short_code(short_code, valid_url)
+            .update_url_by_short_code(
+                short_code,
+                valid_url,
+                expiration,
+                Actor::from(&auth.api_key),
+            )
             .await?;
 
         Ok(ApiResponse::success(response))
     }
 
+    /// Lists every destination a short code pointed to, latest first.
+    pub async fn fetch_url_history(
+        _auth: Authorized<StatsRead>,
+        param: Path<String>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+
+        let history = service.url_service.get_url_history(&short_code).await?;
+
+        Ok(ApiResponse::success(history))
+    }
+
+    pub async fn rollback_url_by_short_code(
+        auth: Authorized<LinksWrite>,
+        param: Path<(String, i32)>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let (code, revision) = param.into_inner();
+        let short_code = ShortCode::new(code)?;
+
+        let url = service
+            .url_service
+            .rollback_url_by_short_code(&short_code, revision, Actor::from(&auth.api_key))
+            .await?;
+
+        Ok(ApiResponse::success(url))
+    }
+
     pub async fn delete_url_by_short_code(
+        _auth: Authorized<LinksDelete>,
         param: Path<String>,
         service: Data<AppServices>,
     ) -> Result<HttpResponse, AppError> {
-        let short_code = ShortCode::new(Some(param.into_inner()))?;
+        let short_code = ShortCode::new(param.into_inner())?;
 
         let _ = service
             .url_service
@@ -147,4 +524,20 @@ impl UrlHandler {
 
         Ok(ApiResponse::<Value>::success_with_no_content())
     }
+
+    /// Takes a link back out of the trash, with its stats.
+    pub async fn restore_url_by_short_code(
+        _auth: Authorized<LinksWrite>,
+        param: Path<String>,
+        service: Data<AppServices>,
+    ) -> Result<HttpResponse, AppError> {
+        let short_code = ShortCode::new(param.into_inner())?;
+
+        let url = service
+            .url_service
+            .restore_url_by_short_code(&short_code)
+            .await?;
+
+        Ok(ApiResponse::success(UrlResponseDto::from(url)))
+    }
 }
@@ -0,0 +1,5 @@
+mod short_code_pool_job;
+mod trash_purge_job;
+
+pub use short_code_pool_job::*;
+pub use trash_purge_job::*;
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use crate::{configuration::ShortCodePoolSettings, features::urls::generators::CodeGenerator};
+
+/// Keeps the short code pool topped up with unused codes.
+pub struct ShortCodePoolJob {
+    generator: Arc<CodeGenerator>,
+    settings: ShortCodePoolSettings,
+}
+
+impl ShortCodePoolJob {
+    pub fn spawn(generator: Arc<CodeGenerator>, settings: ShortCodePoolSettings) {
+        if !settings.enabled {
+            return;
+        }
+
+        let job = Self {
+            generator,
+            settings,
+        };
+
+        tokio::spawn(job.run());
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(self.settings.refill_interval());
+
+        loop {
+            ticker.tick().await;
+
+            match self.generator.refill_pool().await {
+                Ok(0) => {}
+                Ok(added) => tracing::info!("Added {} short codes to the pool", added),
+                Err(error) => tracing::error!("Failed to refill the short code pool: {}", error),
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use crate::{
+    configuration::TrashSettings,
+    features::urls::{
+        repository::UrlRepository,
+        service::{IUrlService, UrlService},
+    },
+};
+
+/// Periodically removes links that have been in the trash past the retention period.
+pub struct TrashPurgeJob {
+    service: Arc<UrlService<UrlRepository>>,
+    settings: TrashSettings,
+}
+
+impl TrashPurgeJob {
+    pub fn spawn(service: Arc<UrlService<UrlRepository>>, settings: TrashSettings) {
+        let job = Self { service, settings };
+
+        tokio::spawn(job.run());
+    }
+
+    async fn run(self) {
+        let mut ticker = tokio::time::interval(self.settings.purge_interval());
+
+        loop {
+            ticker.tick().await;
+
+            match self.service.purge_deleted().await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} deleted links", purged),
+                Err(error) => tracing::error!("Failed to purge deleted links: {}", error),
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub mod dtos;
 pub mod entities;
 pub mod errors;
+pub mod generators;
 pub mod handlers;
+pub mod jobs;
 pub mod models;
 pub mod repository;
 pub mod service;
@@ -0,0 +1,20 @@
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::features::api_keys::models::ApiKey;
+
+/// The API key behind a change to a link.
+#[derive(Debug, Clone, Serialize)]
+pub struct Actor {
+    pub key_id: Uuid,
+    pub name: String,
+}
+
+impl From<&ApiKey> for Actor {
+    fn from(api_key: &ApiKey) -> Self {
+        Self {
+            key_id: api_key.id,
+            name: api_key.name.clone(),
+        }
+    }
+}
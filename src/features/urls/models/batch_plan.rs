@@ -0,0 +1,260 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    configuration::DedupePolicy,
+    features::urls::{
+        errors::UrlError,
+        models::{NewUrl, Url},
+        value_objects::Expiration,
+    },
+};
+
+/// How a batch of new links splits up before anything is written: items
+/// answered up front, links left to insert, and repeats of a destination
+/// created earlier in the batch.
+#[derive(Debug)]
+pub struct BatchPlan {
+    results: Vec<Option<Result<Url, UrlError>>>,
+    /// Links to insert along with their position in the batch.
+    pub pending: Vec<(usize, NewUrl)>,
+    /// Short codes used by the batch, generated ones must avoid them.
+    pub claimed: HashSet<String>,
+    /// Positions of repeated destinations and of the item creating them.
+    repeats: Vec<(usize, usize)>,
+}
+
+impl BatchPlan {
+    /// Answers invalid items, reused links and custom codes already in use.
+    /// `existing` holds the reusable links of known destinations and `taken`
+    /// the requested custom codes found in the database. A link is only
+    /// reused for an item asking for the same destination and expiration.
+    pub fn new(
+        items: Vec<Result<NewUrl, UrlError>>,
+        existing: Vec<Url>,
+        taken: &HashSet<String>,
+        dedupe: DedupePolicy,
+    ) -> Self {
+        let mut plan = Self {
+            results: items.iter().map(|_| None).collect(),
+            pending: Vec::new(),
+            claimed: HashSet::new(),
+            repeats: Vec::new(),
+        };
+        let existing: HashMap<(String, Expiration), Url> = existing
+            .into_iter()
+            .map(|url| {
+                let key = (
+                    url.original_url.as_str().to_string(),
+                    url.expiration.clone(),
+                );
+                (key, url)
+            })
+            .collect();
+        let mut creators: HashMap<(String, Expiration), usize> = HashMap::new();
+
+        for (index, item) in items.into_iter().enumerate() {
+            let item = match item {
+                Ok(item) => item,
+                Err(error) => {
+                    plan.answer(index, Err(error));
+                    continue;
+                }
+            };
+
+            // Like single creation, a known destination may return its existing
+            // link, or the one created for it earlier in the batch
+            let destination = (
+                item.original_url.as_str().to_string(),
+                item.expiration.clone(),
+            );
+
+            if dedupe.reuses(item.short_code.is_some()) {
+                if let Some(url) = existing.get(&destination) {
+                    plan.answer(index, Ok(url.clone()));
+                    continue;
+                }
+
+                if let Some(&creator) = creators.get(&destination) {
+                    plan.repeats.push((index, creator));
+                    continue;
+                }
+            }
+
+            if let Some(short_code) = &item.short_code
+                && (taken.contains(short_code.as_str())
+                    || !plan.claimed.insert(short_code.as_str().to_string()))
+            {
+                plan.answer(index, Err(UrlError::code_in_use(short_code)));
+                continue;
+            }
+
+            creators.entry(destination).or_insert(index);
+            plan.pending.push((index, item));
+        }
+
+        plan
+    }
+
+    pub fn answer(&mut self, index: usize, result: Result<Url, UrlError>) {
+        self.results[index] = Some(result);
+    }
+
+    pub fn has_failures(&self) -> bool {
+        self.results
+            .iter()
+            .any(|result| matches!(result, Some(Err(_))))
+    }
+
+    /// Reports every item not answered yet as aborted.
+    pub fn abort(self) -> Vec<Result<Url, UrlError>> {
+        self.results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(UrlError::BatchAborted)))
+            .collect()
+    }
+
+    /// Gives each repeat the outcome of the item creating its destination.
+    pub fn finish(mut self) -> Vec<Result<Url, UrlError>> {
+        for (index, creator) in std::mem::take(&mut self.repeats) {
+            let result = match &self.results[creator] {
+                Some(Ok(url)) => Ok(url.clone()),
+                _ => Err(UrlError::Duplicate(format!(
+                    "Same destination as item {creator}, which was not created"
+                ))),
+            };
+            self.answer(index, result);
+        }
+
+        self.abort()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::urls::value_objects::{ShortCode, ValidUrl};
+
+    fn item(original_url: &str, short_code: Option<&str>) -> Result<NewUrl, UrlError> {
+        Ok(NewUrl {
+            original_url: ValidUrl::new(original_url.to_string()).unwrap(),
+            short_code: short_code.map(|code| ShortCode::new(code.to_string()).unwrap()),
+            expiration: Expiration::default(),
+        })
+    }
+
+    fn url(original_url: &str, short_code: &str) -> Url {
+        Url::new(
+            ValidUrl::new(original_url.to_string()).unwrap(),
+            ShortCode::new(short_code.to_string()).unwrap(),
+            Expiration::default(),
+        )
+    }
+
+    fn short_code(result: &Result<Url, UrlError>) -> &str {
+        result.as_ref().unwrap().short_code.as_str()
+    }
+
+    #[test]
+    fn reuses_known_destinations_and_repeats_within_the_batch() {
+        let existing = vec![url("https://known.com/", "known1")];
+        let items = vec![
+            item("https://known.com/", None),
+            item("https://new.com/", None),
+            item("https://new.com/", None),
+        ];
+
+        let mut plan = BatchPlan::new(items, existing, &HashSet::new(), DedupePolicy::Always);
+
+        assert_eq!(plan.pending.len(), 1);
+        assert_eq!(plan.pending[0].0, 1);
+
+        plan.answer(1, Ok(url("https://new.com/", "fresh1")));
+        let results = plan.finish();
+
+        assert_eq!(short_code(&results[0]), "known1");
+        assert_eq!(short_code(&results[1]), "fresh1");
+        assert_eq!(short_code(&results[2]), "fresh1");
+    }
+
+    #[test]
+    fn reuses_only_links_with_the_requested_expiration() {
+        let existing = vec![url("https://known.com/", "known1")];
+        let mut limited = item("https://known.com/", None).unwrap();
+        limited.expiration = Expiration::new(None, Some(10)).unwrap();
+        let items = vec![Ok(limited.clone()), Ok(limited)];
+
+        let plan = BatchPlan::new(items, existing, &HashSet::new(), DedupePolicy::Always);
+
+        assert_eq!(plan.pending.len(), 1);
+        assert_eq!(plan.repeats, vec![(1, 0)]);
+    }
+
+    #[test]
+    fn creates_every_item_when_not_deduplicating() {
+        let existing = vec![url("https://known.com/", "known1")];
+        let items = vec![
+            item("https://known.com/", None),
+            item("https://known.com/", None),
+        ];
+
+        let plan = BatchPlan::new(items, existing, &HashSet::new(), DedupePolicy::Never);
+
+        assert_eq!(plan.pending.len(), 2);
+    }
+
+    #[test]
+    fn reports_errors_per_item() {
+        let taken = HashSet::from([String::from("taken1")]);
+        let items = vec![
+            Err(UrlError::InvalidUrl(String::from("not a url"))),
+            item("https://a.com/", Some("taken1")),
+            item("https://b.com/", Some("mine1")),
+            item("https://c.com/", Some("mine1")),
+            item("https://d.com/", None),
+        ];
+
+        let mut plan = BatchPlan::new(items, Vec::new(), &taken, DedupePolicy::Never);
+        let pending: Vec<usize> = plan.pending.iter().map(|(index, _)| *index).collect();
+
+        assert_eq!(pending, vec![2, 4]);
+        assert!(plan.claimed.contains("mine1"));
+
+        plan.answer(2, Ok(url("https://b.com/", "mine1")));
+        plan.answer(4, Ok(url("https://d.com/", "gen001")));
+        let results = plan.finish();
+
+        assert!(matches!(results[0], Err(UrlError::InvalidUrl(_))));
+        assert!(matches!(results[1], Err(UrlError::Duplicate(_))));
+        assert_eq!(short_code(&results[2]), "mine1");
+        assert!(matches!(results[3], Err(UrlError::Duplicate(_))));
+        assert_eq!(short_code(&results[4]), "gen001");
+    }
+
+    #[test]
+    fn repeats_fail_with_their_creator() {
+        let items = vec![item("https://a.com/", None), item("https://a.com/", None)];
+
+        let mut plan = BatchPlan::new(items, Vec::new(), &HashSet::new(), DedupePolicy::Always);
+        plan.answer(0, Err(UrlError::BatchAborted));
+        let results = plan.finish();
+
+        assert!(matches!(results[1], Err(UrlError::Duplicate(_))));
+    }
+
+    #[test]
+    fn aborting_leaves_pending_items_uncreated() {
+        let items = vec![
+            Err(UrlError::InvalidUrl(String::from("not a url"))),
+            item("https://a.com/", None),
+        ];
+
+        let plan = BatchPlan::new(items, Vec::new(), &HashSet::new(), DedupePolicy::Always);
+
+        assert!(plan.has_failures());
+
+        let results = plan.abort();
+
+        assert!(matches!(results[0], Err(UrlError::InvalidUrl(_))));
+        assert!(matches!(results[1], Err(UrlError::BatchAborted)));
+    }
+}
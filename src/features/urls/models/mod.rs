@@ -1,3 +1,13 @@
+mod actor;
+mod batch_plan;
+mod new_url;
 mod url;
+mod url_page;
+mod url_revision;
 
+pub use actor::*;
+pub use batch_plan::*;
+pub use new_url::*;
 pub use url::*;
+pub use url_page::*;
+pub use url_revision::*;
@@ -0,0 +1,10 @@
+use crate::features::urls::value_objects::{Expiration, ShortCode, ValidUrl};
+
+/// A validated link waiting to be created, without a short code when one
+/// should be generated.
+#[derive(Debug, Clone)]
+pub struct NewUrl {
+    pub original_url: ValidUrl,
+    pub short_code: Option<ShortCode>,
+    pub expiration: Expiration,
+}
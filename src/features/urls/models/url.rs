@@ -1,30 +1,46 @@
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
-use crate::features::urls::value_objects::{ShortCode, ValidUrl};
+use crate::features::urls::value_objects::{Expiration, ExpirationUpdate, ShortCode, ValidUrl};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct Url {
     pub id: Option<Uuid>,
     pub original_url: ValidUrl,
     pub short_code: ShortCode,
+    pub expiration: Expiration,
+    /// Current destination revision, `None` until saved.
+    pub revision: Option<i32>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl Url {
-    pub fn new(original_url: ValidUrl, short_code: ShortCode) -> Self {
+    pub fn new(original_url: ValidUrl, short_code: ShortCode, expiration: Expiration) -> Self {
         Self {
             id: None,
             original_url,
             short_code,
+            expiration,
+            revision: None,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
+            deleted_at: None,
         }
     }
 
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
     pub fn update_url(&mut self, new_url: ValidUrl) {
         self.original_url = new_url;
         self.updated_at = Some(Utc::now())
     }
+
+    pub fn update_expiration(&mut self, expiration: ExpirationUpdate) {
+        self.expiration.merge(expiration);
+        self.updated_at = Some(Utc::now())
+    }
 }
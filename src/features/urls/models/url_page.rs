@@ -0,0 +1,14 @@
+use crate::features::urls::{models::Url, value_objects::UrlCursor};
+
+/// A link together with its total click count.
+#[derive(Debug, Clone)]
+pub struct ListedUrl {
+    pub url: Url,
+    pub access_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct UrlPage {
+    pub urls: Vec<ListedUrl>,
+    pub next_cursor: Option<UrlCursor>,
+}
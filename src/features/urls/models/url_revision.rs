@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::features::urls::models::Actor;
+
+/// A destination a link pointed to from `created_at` until the next revision.
+#[derive(Debug, Clone, Serialize)]
+pub struct UrlRevision {
+    pub revision: i32,
+    pub original_url: String,
+    /// `None` for history recorded before actors were tracked.
+    pub actor: Option<Actor>,
+    /// The earlier revision this one rolled back to.
+    pub rollback_of: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
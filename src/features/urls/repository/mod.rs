@@ -1,3 +1,7 @@
+mod short_code_counter_repository;
+mod short_code_pool_repository;
 mod url_repository;
 
+pub use short_code_counter_repository::*;
+pub use short_code_pool_repository::*;
 pub use url_repository::*;
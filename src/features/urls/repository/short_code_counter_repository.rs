@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use crate::{features::urls::errors::UrlError, infrastructure::database::connection::DatabasePool};
+
+/// Hands out values of a database sequence shared by every instance.
+pub struct ShortCodeCounterRepository {
+    database: Arc<DatabasePool>,
+}
+
+impl ShortCodeCounterRepository {
+    pub fn new(database: Arc<DatabasePool>) -> Self {
+        Self { database }
+    }
+
+    /// Returns `count` fresh values, never handed out before.
+    pub async fn next_values(&self, count: usize) -> Result<Vec<u64>, UrlError> {
+        let values = sqlx::query_scalar!(
+            r#"SELECT nextval('short_code_counter_seq') AS "value!" FROM generate_series(1, $1::bigint)"#,
+            count as i64
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(values.into_iter().map(|value| value as u64).collect())
+    }
+}
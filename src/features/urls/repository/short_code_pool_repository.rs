@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use crate::{features::urls::errors::UrlError, infrastructure::database::connection::DatabasePool};
+
+pub trait IShortCodePoolRepository: Send + Sync {
+    /// Removes and returns up to `count` codes. Rows locked by a concurrent
+    /// claim are skipped, so two creates never get the same code.
+    fn claim(&self, count: usize) -> impl Future<Output = Result<Vec<String>, UrlError>> + Send;
+    /// Adds the codes not already pooled or used by a link, returns how many were added.
+    fn add(&self, short_codes: &[String]) -> impl Future<Output = Result<u64, UrlError>> + Send;
+    fn depth(&self) -> impl Future<Output = Result<i64, UrlError>> + Send;
+}
+
+/// Stores pre-generated short codes waiting to be claimed by a create.
+pub struct ShortCodePoolRepository {
+    database: Arc<DatabasePool>,
+}
+
+impl ShortCodePoolRepository {
+    pub fn new(database: Arc<DatabasePool>) -> Self {
+        Self { database }
+    }
+}
+
+impl IShortCodePoolRepository for ShortCodePoolRepository {
+    async fn claim(&self, count: usize) -> Result<Vec<String>, UrlError> {
+        let codes = sqlx::query_scalar!(
+            r#"
+            DELETE FROM short_code_pool
+            WHERE short_code IN (
+                SELECT short_code FROM short_code_pool
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING short_code
+            "#,
+            count as i64
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
+    async fn add(&self, short_codes: &[String]) -> Result<u64, UrlError> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO short_code_pool (short_code)
+            SELECT code FROM UNNEST($1::varchar[]) AS code
+            WHERE NOT EXISTS (SELECT 1 FROM urls WHERE urls.short_code = code)
+            ON CONFLICT DO NOTHING
+            "#,
+            short_codes
+        )
+        .execute(&self.database.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn depth(&self) -> Result<i64, UrlError> {
+        let depth = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM short_code_pool"#)
+            .fetch_one(&self.database.pool)
+            .await?;
+
+        Ok(depth)
+    }
+}
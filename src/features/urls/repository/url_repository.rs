@@ -1,46 +1,111 @@
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
     features::urls::{
-        entities::UrlEntity,
+        entities::{ListedUrlEntity, UrlEntity, UrlRevisionEntity},
         errors::UrlError,
-        models::Url,
-        value_objects::{ShortCode, ValidUrl},
+        models::{Actor, Url, UrlPage, UrlRevision},
+        value_objects::{
+            Expiration, ShortCode, SortOrder, UrlCursor, UrlListQuery, UrlSort, ValidUrl,
+        },
     },
     infrastructure::database::connection::DatabasePool,
 };
 
 pub trait IUrlRepository: Send + Sync {
-    fn save(&self, url: &Url) -> impl Future<Output = Result<Url, UrlError>> + Send;
+    /// Inserts a link and records its destination as a revision by `actor`.
+    /// Returns `None` without touching the existing link when the short code is taken.
+    /// A pooled copy of the code is removed so the pool never hands it out.
+    fn insert(
+        &self,
+        url: &Url,
+        actor: &Actor,
+    ) -> impl Future<Output = Result<Option<Url>, UrlError>> + Send;
+    /// Inserts a link under a code claimed from the short code pool in the
+    /// same statement. Returns `None` when the pool is empty.
+    fn insert_with_pooled_code(
+        &self,
+        original_url: &ValidUrl,
+        expiration: &Expiration,
+        actor: &Actor,
+    ) -> impl Future<Output = Result<Option<Url>, UrlError>> + Send;
     fn find_by_id(&self, id: Uuid) -> impl Future<Output = Result<Option<Uuid>, UrlError>> + Send;
     fn find_by_short_code(
         &self,
         short_code: &ShortCode,
     ) -> impl Future<Output = Result<Option<Url>, UrlError>> + Send; // update the short code type
+    /// Oldest link to a destination that can still be visited and has exactly
+    /// `expiration`, several links may share one destination.
     fn find_by_original_url(
         &self,
         original_url: &ValidUrl,
+        expiration: &Expiration,
     ) -> impl Future<Output = Result<Option<Url>, UrlError>> + Send;
+    /// Oldest link that can still be visited for each destination and
+    /// expiration pair.
+    fn find_by_original_urls(
+        &self,
+        destinations: &[(String, Expiration)],
+    ) -> impl Future<Output = Result<Vec<Url>, UrlError>> + Send;
+    fn find_taken_short_codes(
+        &self,
+        short_codes: &[String],
+    ) -> impl Future<Output = Result<Vec<String>, UrlError>> + Send;
+    /// Inserts links in a single transaction, skipping those whose short code
+    /// already exists. With `atomic` the transaction is rolled back instead when
+    /// any link is skipped, and no link is returned. Like `insert`, pooled copies
+    /// of the codes are removed.
+    fn insert_many(
+        &self,
+        urls: &[Url],
+        atomic: bool,
+        actor: &Actor,
+    ) -> impl Future<Output = Result<Vec<Url>, UrlError>> + Send;
+    /// Updates a link, recording a new revision when its destination changes.
     fn update(
         &self,
-        url: &mut Url,
-        valid_url: ValidUrl,
+        url: &Url,
+        actor: &Actor,
+        rollback_of: Option<i32>,
     ) -> impl Future<Output = Result<Url, UrlError>> + Send;
+    fn find_revision(
+        &self,
+        url_id: Uuid,
+        revision: i32,
+    ) -> impl Future<Output = Result<Option<UrlRevision>, UrlError>> + Send;
+    /// Destination history of a link, latest revision first.
+    fn list_revisions(
+        &self,
+        url_id: Uuid,
+    ) -> impl Future<Output = Result<Vec<UrlRevision>, UrlError>> + Send;
+    /// Moves a link to the trash, its stats are kept until it is purged.
     fn delete_by_short_code(
         &self,
         short_code: &ShortCode,
     ) -> impl Future<Output = Result<bool, UrlError>> + Send;
-    fn exists_by_short_code(
+    fn restore_by_short_code(
         &self,
         short_code: &ShortCode,
-    ) -> impl Future<Output = Result<bool, UrlError>> + Send;
+    ) -> impl Future<Output = Result<Option<Url>, UrlError>> + Send;
+    /// Permanently removes links deleted before the cutoff, with their stats.
+    fn purge_deleted_before(
+        &self,
+        cutoff: DateTime<Utc>,
+    ) -> impl Future<Output = Result<u64, UrlError>> + Send;
+    /// Human clicks recorded so far, what the `max_clicks` quota is checked against.
+    fn count_clicks(&self, url_id: Uuid) -> impl Future<Output = Result<i64, UrlError>> + Send;
+    fn list(&self, query: &UrlListQuery) -> impl Future<Output = Result<UrlPage, UrlError>> + Send;
 }
 
 pub struct UrlRepository {
     database: Arc<DatabasePool>,
 }
 
+/// Rows per `INSERT` statement, keeping bulk inserts well under the bind limit.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
 impl UrlRepository {
     pub fn new(database: Arc<DatabasePool>) -> Self {
         Self { database }
@@ -48,28 +113,101 @@ impl UrlRepository {
 }
 
 impl IUrlRepository for UrlRepository {
-    async fn save(&self, url: &Url) -> Result<Url, UrlError> {
+    async fn insert(&self, url: &Url, actor: &Actor) -> Result<Option<Url>, UrlError> {
         let db_url = UrlEntity::from_domain(url);
 
-        let saved_url = sqlx::query_as!(
+        // The unique short code settles concurrent creations, the loser gets no row back
+        let inserted_url = sqlx::query_as!(
             UrlEntity,
             r#"
-            INSERT INTO urls (original_url, short_code, created_at, updated_at)
-            VALUES ($1, $2, $3, $4)
-            ON CONFLICT (short_code) DO UPDATE
-            SET original_url = EXCLUDED.original_url,
-                updated_at = EXCLUDED.updated_at
-            RETURNING *
+            WITH inserted AS (
+                INSERT INTO urls (original_url, short_code, created_at, updated_at, expires_at, max_clicks)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (short_code) DO NOTHING
+                RETURNING *
+            ),
+            unpooled AS (
+                DELETE FROM short_code_pool WHERE short_code = $2
+            ),
+            revision AS (
+                INSERT INTO url_revisions (url_id, revision, original_url, actor_key_id, actor, created_at)
+                SELECT id, revision, original_url, $7, $8, created_at FROM inserted
+            ),
+            stats AS (
+                INSERT INTO url_stats (url_id) SELECT id FROM inserted
+            )
+            SELECT id AS "id!", original_url AS "original_url!", short_code AS "short_code!",
+                   created_at AS "created_at!", updated_at AS "updated_at!", expires_at, max_clicks,
+                   deleted_at, revision AS "revision!"
+            FROM inserted
             "#,
             db_url.original_url,
             db_url.short_code,
             db_url.created_at,
-            db_url.updated_at
+            db_url.updated_at,
+            db_url.expires_at,
+            db_url.max_clicks,
+            actor.key_id,
+            actor.name
         )
-        .fetch_one(&self.database.pool)
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        inserted_url.map(|db_url| db_url.to_domain()).transpose()
+    }
+
+    async fn insert_with_pooled_code(
+        &self,
+        original_url: &ValidUrl,
+        expiration: &Expiration,
+        actor: &Actor,
+    ) -> Result<Option<Url>, UrlError> {
+        let now = Utc::now();
+
+        // Pooled codes were checked against existing links, a conflict only
+        // happens when a custom code raced the refill and leaves no row either
+        let inserted_url = sqlx::query_as!(
+            UrlEntity,
+            r#"
+            WITH claimed AS (
+                DELETE FROM short_code_pool
+                WHERE short_code = (
+                    SELECT short_code FROM short_code_pool
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING short_code
+            ),
+            inserted AS (
+                INSERT INTO urls (original_url, short_code, created_at, updated_at, expires_at, max_clicks)
+                SELECT $1::text, short_code, $2::timestamptz, $2::timestamptz, $3::timestamptz, $4::int
+                FROM claimed
+                ON CONFLICT (short_code) DO NOTHING
+                RETURNING *
+            ),
+            revision AS (
+                INSERT INTO url_revisions (url_id, revision, original_url, actor_key_id, actor, created_at)
+                SELECT id, revision, original_url, $5, $6, created_at FROM inserted
+            ),
+            stats AS (
+                INSERT INTO url_stats (url_id) SELECT id FROM inserted
+            )
+            SELECT id AS "id!", original_url AS "original_url!", short_code AS "short_code!",
+                   created_at AS "created_at!", updated_at AS "updated_at!", expires_at, max_clicks,
+                   deleted_at, revision AS "revision!"
+            FROM inserted
+            "#,
+            original_url.as_ref(),
+            now,
+            expiration.expires_at(),
+            expiration.max_clicks(),
+            actor.key_id,
+            actor.name
+        )
+        .fetch_optional(&self.database.pool)
         .await?;
 
-        saved_url.to_domain()
+        inserted_url.map(|db_url| db_url.to_domain()).transpose()
     }
 
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Uuid>, UrlError> {
@@ -83,7 +221,11 @@ impl IUrlRepository for UrlRepository {
     async fn find_by_short_code(&self, short_code: &ShortCode) -> Result<Option<Url>, UrlError> {
         let result = sqlx::query_as!(
             UrlEntity,
-            "SELECT * FROM urls WHERE short_code = $1",
+            r#"
+            SELECT id, original_url, short_code, created_at, updated_at, expires_at, max_clicks,
+                   deleted_at, revision
+            FROM urls WHERE short_code = $1
+            "#,
             short_code.as_str()
         )
         .fetch_optional(&self.database.pool)
@@ -92,11 +234,29 @@ impl IUrlRepository for UrlRepository {
         result.map(|db_url| db_url.to_domain()).transpose()
     }
 
-    async fn find_by_original_url(&self, original_url: &ValidUrl) -> Result<Option<Url>, UrlError> {
+    async fn find_by_original_url(
+        &self,
+        original_url: &ValidUrl,
+        expiration: &Expiration,
+    ) -> Result<Option<Url>, UrlError> {
         let result = sqlx::query_as!(
             UrlEntity,
-            "SELECT * FROM urls WHERE original_url = $1",
-            original_url.as_ref()
+            r#"
+            SELECT url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                   url.expires_at, url.max_clicks, url.deleted_at, url.revision
+            FROM urls url
+            LEFT JOIN url_stats stats ON stats.url_id = url.id
+            WHERE url.original_url = $1 AND url.deleted_at IS NULL
+              AND url.expires_at IS NOT DISTINCT FROM $2
+              AND url.max_clicks IS NOT DISTINCT FROM $3
+              AND (url.expires_at IS NULL OR url.expires_at > now())
+              AND (url.max_clicks IS NULL OR COALESCE(stats.access_count, 0) < url.max_clicks)
+            ORDER BY url.created_at, url.id
+            LIMIT 1
+            "#,
+            original_url.as_ref(),
+            expiration.expires_at(),
+            expiration.max_clicks()
         )
         .fetch_optional(&self.database.pool)
         .await?;
@@ -104,9 +264,145 @@ impl IUrlRepository for UrlRepository {
         result.map(|db_url| db_url.to_domain()).transpose()
     }
 
+    async fn find_by_original_urls(
+        &self,
+        destinations: &[(String, Expiration)],
+    ) -> Result<Vec<Url>, UrlError> {
+        let mut original_urls = Vec::with_capacity(destinations.len());
+        let mut expires_at = Vec::with_capacity(destinations.len());
+        let mut max_clicks = Vec::with_capacity(destinations.len());
+
+        for (original_url, expiration) in destinations {
+            original_urls.push(original_url.clone());
+            expires_at.push(expiration.expires_at());
+            max_clicks.push(expiration.max_clicks());
+        }
+
+        let result = sqlx::query_as!(
+            UrlEntity,
+            r#"
+            SELECT DISTINCT ON (url.original_url, url.expires_at, url.max_clicks)
+                   url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                   url.expires_at, url.max_clicks, url.deleted_at, url.revision
+            FROM UNNEST($1::text[], $2::timestamptz[], $3::int[])
+                AS wanted(original_url, expires_at, max_clicks)
+            JOIN urls url
+                ON url.original_url = wanted.original_url
+               AND url.expires_at IS NOT DISTINCT FROM wanted.expires_at
+               AND url.max_clicks IS NOT DISTINCT FROM wanted.max_clicks
+            LEFT JOIN url_stats stats ON stats.url_id = url.id
+            WHERE url.deleted_at IS NULL
+              AND (url.expires_at IS NULL OR url.expires_at > now())
+              AND (url.max_clicks IS NULL OR COALESCE(stats.access_count, 0) < url.max_clicks)
+            ORDER BY url.original_url, url.expires_at, url.max_clicks, url.created_at, url.id
+            "#,
+            &original_urls,
+            &expires_at as &[Option<DateTime<Utc>>],
+            &max_clicks as &[Option<i32>]
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        result.iter().map(UrlEntity::to_domain).collect()
+    }
+
+    async fn find_taken_short_codes(
+        &self,
+        short_codes: &[String],
+    ) -> Result<Vec<String>, UrlError> {
+        let result = sqlx::query!(
+            "SELECT short_code FROM urls WHERE short_code = ANY($1)",
+            short_codes
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(result.into_iter().map(|row| row.short_code).collect())
+    }
+
+    async fn insert_many(
+        &self,
+        urls: &[Url],
+        atomic: bool,
+        actor: &Actor,
+    ) -> Result<Vec<Url>, UrlError> {
+        let mut transaction = self.database.pool.begin().await?;
+        let mut inserted = Vec::with_capacity(urls.len());
+
+        for chunk in urls.chunks(INSERT_CHUNK_SIZE) {
+            let entities: Vec<UrlEntity> = chunk.iter().map(UrlEntity::from_domain).collect();
+            let original_urls: Vec<String> = entities
+                .iter()
+                .map(|url| url.original_url.clone())
+                .collect();
+            let short_codes: Vec<String> =
+                entities.iter().map(|url| url.short_code.clone()).collect();
+            let created_at: Vec<DateTime<Utc>> =
+                entities.iter().map(|url| url.created_at).collect();
+            let updated_at: Vec<DateTime<Utc>> =
+                entities.iter().map(|url| url.updated_at).collect();
+            let expires_at: Vec<Option<DateTime<Utc>>> =
+                entities.iter().map(|url| url.expires_at).collect();
+            let max_clicks: Vec<Option<i32>> = entities.iter().map(|url| url.max_clicks).collect();
+
+            let rows = sqlx::query_as!(
+                UrlEntity,
+                r#"
+                WITH inserted AS (
+                    INSERT INTO urls (original_url, short_code, created_at, updated_at, expires_at, max_clicks)
+                    SELECT * FROM UNNEST($1::text[], $2::text[], $3::timestamptz[], $4::timestamptz[],
+                                         $5::timestamptz[], $6::int[])
+                    ON CONFLICT DO NOTHING
+                    RETURNING *
+                ),
+                unpooled AS (
+                    DELETE FROM short_code_pool WHERE short_code = ANY($2)
+                ),
+                revision AS (
+                    INSERT INTO url_revisions (url_id, revision, original_url, actor_key_id, actor, created_at)
+                    SELECT id, revision, original_url, $7, $8, created_at FROM inserted
+                ),
+                stats AS (
+                    INSERT INTO url_stats (url_id) SELECT id FROM inserted
+                )
+                SELECT id AS "id!", original_url AS "original_url!", short_code AS "short_code!",
+                       created_at AS "created_at!", updated_at AS "updated_at!", expires_at,
+                       max_clicks, deleted_at, revision AS "revision!"
+                FROM inserted
+                "#,
+                &original_urls,
+                &short_codes,
+                &created_at,
+                &updated_at,
+                &expires_at as &[Option<DateTime<Utc>>],
+                &max_clicks as &[Option<i32>],
+                actor.key_id,
+                actor.name
+            )
+            .fetch_all(&mut *transaction)
+            .await?;
+
+            if atomic && rows.len() < chunk.len() {
+                transaction.rollback().await?;
+                return Ok(Vec::new());
+            }
+
+            for row in rows {
+                inserted.push(row.to_domain()?);
+            }
+        }
+
+        transaction.commit().await?;
+
+        Ok(inserted)
+    }
+
     async fn delete_by_short_code(&self, short_code: &ShortCode) -> Result<bool, UrlError> {
         let result = sqlx::query!(
-            "DELETE FROM urls WHERE short_code = $1",
+            r#"
+            UPDATE urls SET deleted_at = now(), updated_at = now()
+            WHERE short_code = $1 AND deleted_at IS NULL
+            "#,
             short_code.as_str()
         )
         .execute(&self.database.pool)
@@ -115,29 +411,321 @@ impl IUrlRepository for UrlRepository {
         Ok(result.rows_affected() > 0)
     }
 
-    async fn exists_by_short_code(&self, short_code: &ShortCode) -> Result<bool, UrlError> {
-        let result = self.find_by_short_code(short_code).await?;
+    async fn restore_by_short_code(&self, short_code: &ShortCode) -> Result<Option<Url>, UrlError> {
+        let result = sqlx::query_as!(
+            UrlEntity,
+            r#"
+            UPDATE urls SET deleted_at = NULL, updated_at = now()
+            WHERE short_code = $1 AND deleted_at IS NOT NULL
+            RETURNING id, original_url, short_code, created_at, updated_at, expires_at, max_clicks,
+                      deleted_at, revision
+            "#,
+            short_code.as_str()
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        result.map(|db_url| db_url.to_domain()).transpose()
+    }
+
+    async fn purge_deleted_before(&self, cutoff: DateTime<Utc>) -> Result<u64, UrlError> {
+        let mut purged = 0;
 
-        Ok(result.is_some())
+        // Each link cascades to all of its click logs, so batches stay small
+        loop {
+            let result = sqlx::query!(
+                r#"
+                DELETE FROM urls
+                WHERE id IN (
+                    SELECT id FROM urls WHERE deleted_at < $1 LIMIT 100
+                )
+                "#,
+                cutoff
+            )
+            .execute(&self.database.pool)
+            .await?;
+
+            purged += result.rows_affected();
+
+            if result.rows_affected() < 100 {
+                return Ok(purged);
+            }
+        }
     }
 
-    async fn update(&self, url: &mut Url, valid_url: ValidUrl) -> Result<Url, UrlError> {
-        url.update_url(valid_url);
+    async fn count_clicks(&self, url_id: Uuid) -> Result<i64, UrlError> {
+        let result = sqlx::query!(
+            "SELECT access_count FROM url_stats WHERE url_id = $1",
+            url_id
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        Ok(result.map_or(0, |row| i64::from(row.access_count)))
+    }
 
+    async fn update(
+        &self,
+        url: &Url,
+        actor: &Actor,
+        rollback_of: Option<i32>,
+    ) -> Result<Url, UrlError> {
+        // The revision only moves when the destination does, so an unchanged
+        // destination conflicts with its existing revision and isn't recorded
         let result = sqlx::query_as!(
             UrlEntity,
             r#"
-            UPDATE urls SET original_url = $1, updated_at = $3
-            WHERE short_code = $2
-            RETURNING *
+            WITH updated AS (
+                UPDATE urls
+                SET original_url = $1::text, updated_at = $3, expires_at = $4, max_clicks = $5,
+                    revision = CASE WHEN original_url = $1::text THEN revision ELSE revision + 1 END
+                WHERE short_code = $2
+                RETURNING *
+            ),
+            revision AS (
+                INSERT INTO url_revisions
+                    (url_id, revision, original_url, actor_key_id, actor, rollback_of, created_at)
+                SELECT id, revision, original_url, $6, $7, $8, updated_at FROM updated
+                ON CONFLICT (url_id, revision) DO NOTHING
+            )
+            SELECT id AS "id!", original_url AS "original_url!", short_code AS "short_code!",
+                   created_at AS "created_at!", updated_at AS "updated_at!", expires_at, max_clicks,
+                   deleted_at, revision AS "revision!"
+            FROM updated
             "#,
             url.original_url.as_str(),
             url.short_code.as_str(),
-            url.updated_at
+            url.updated_at,
+            url.expiration.expires_at(),
+            url.expiration.max_clicks(),
+            actor.key_id,
+            actor.name,
+            rollback_of
         )
         .fetch_one(&self.database.pool)
         .await?;
 
         result.to_domain()
     }
+
+    async fn find_revision(
+        &self,
+        url_id: Uuid,
+        revision: i32,
+    ) -> Result<Option<UrlRevision>, UrlError> {
+        let result = sqlx::query_as!(
+            UrlRevisionEntity,
+            r#"
+            SELECT revision, original_url, actor_key_id, actor, rollback_of, created_at
+            FROM url_revisions
+            WHERE url_id = $1 AND revision = $2
+            "#,
+            url_id,
+            revision
+        )
+        .fetch_optional(&self.database.pool)
+        .await?;
+
+        Ok(result.map(UrlRevisionEntity::to_domain))
+    }
+
+    async fn list_revisions(&self, url_id: Uuid) -> Result<Vec<UrlRevision>, UrlError> {
+        let result = sqlx::query_as!(
+            UrlRevisionEntity,
+            r#"
+            SELECT revision, original_url, actor_key_id, actor, rollback_of, created_at
+            FROM url_revisions
+            WHERE url_id = $1
+            ORDER BY revision DESC
+            "#,
+            url_id
+        )
+        .fetch_all(&self.database.pool)
+        .await?;
+
+        Ok(result
+            .into_iter()
+            .map(UrlRevisionEntity::to_domain)
+            .collect())
+    }
+
+    async fn list(&self, query: &UrlListQuery) -> Result<UrlPage, UrlError> {
+        let cursor = query.cursor();
+        let created_after = cursor
+            .filter(|_| query.sort() == UrlSort::CreatedAt)
+            .map(|cursor| {
+                DateTime::from_timestamp_micros(cursor.value())
+                    .ok_or_else(|| UrlError::InvalidQuery("Invalid cursor".into()))
+            })
+            .transpose()?;
+
+        // One static query per sort key and order so each walks its index:
+        // (created_at, id) on urls, (access_count, url_id) on url_stats. A
+        // domain and its subdomains are one range of the reversed domain
+        // index, `/` sorting right after `.`. One extra row tells whether
+        // another page follows
+        let mut rows = match (query.sort(), query.order()) {
+            (UrlSort::CreatedAt, SortOrder::Desc) => {
+                sqlx::query_as!(
+                    ListedUrlEntity,
+                    r#"
+                    SELECT url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                           url.expires_at, url.max_clicks, url.deleted_at, url.revision,
+                           COALESCE(stats.access_count, 0)::bigint AS "access_count!"
+                    FROM urls url
+                    LEFT JOIN url_stats stats ON stats.url_id = url.id
+                    WHERE (url.deleted_at IS NOT NULL) = $9
+                      AND ($3::text IS NULL OR url.original_url ILIKE $3)
+                      AND ($4::text IS NULL OR url.short_code LIKE $4)
+                      AND ($5::timestamptz IS NULL OR url.created_at >= $5)
+                      AND ($6::timestamptz IS NULL OR url.created_at < $6)
+                      AND ($7::text IS NULL
+                           OR reverse(url.domain) COLLATE "C" = reverse($7)
+                           OR (reverse(url.domain) COLLATE "C" > reverse($7) || '.'
+                               AND reverse(url.domain) COLLATE "C" < reverse($7) || '/'))
+                      AND ($1::timestamptz IS NULL OR (url.created_at, url.id) < ($1, $2::uuid))
+                    ORDER BY url.created_at DESC, url.id DESC
+                    LIMIT $8
+                    "#,
+                    created_after,
+                    cursor.map(|cursor| cursor.id()),
+                    query.search_pattern(),
+                    query.code_prefix_pattern(),
+                    query.from(),
+                    query.to(),
+                    query.domain(),
+                    query.limit() + 1,
+                    query.trashed()
+                )
+                .fetch_all(&self.database.pool)
+                .await?
+            }
+            (UrlSort::CreatedAt, SortOrder::Asc) => {
+                sqlx::query_as!(
+                    ListedUrlEntity,
+                    r#"
+                    SELECT url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                           url.expires_at, url.max_clicks, url.deleted_at, url.revision,
+                           COALESCE(stats.access_count, 0)::bigint AS "access_count!"
+                    FROM urls url
+                    LEFT JOIN url_stats stats ON stats.url_id = url.id
+                    WHERE (url.deleted_at IS NOT NULL) = $9
+                      AND ($3::text IS NULL OR url.original_url ILIKE $3)
+                      AND ($4::text IS NULL OR url.short_code LIKE $4)
+                      AND ($5::timestamptz IS NULL OR url.created_at >= $5)
+                      AND ($6::timestamptz IS NULL OR url.created_at < $6)
+                      AND ($7::text IS NULL
+                           OR reverse(url.domain) COLLATE "C" = reverse($7)
+                           OR (reverse(url.domain) COLLATE "C" > reverse($7) || '.'
+                               AND reverse(url.domain) COLLATE "C" < reverse($7) || '/'))
+                      AND ($1::timestamptz IS NULL OR (url.created_at, url.id) > ($1, $2::uuid))
+                    ORDER BY url.created_at ASC, url.id ASC
+                    LIMIT $8
+                    "#,
+                    created_after,
+                    cursor.map(|cursor| cursor.id()),
+                    query.search_pattern(),
+                    query.code_prefix_pattern(),
+                    query.from(),
+                    query.to(),
+                    query.domain(),
+                    query.limit() + 1,
+                    query.trashed()
+                )
+                .fetch_all(&self.database.pool)
+                .await?
+            }
+            (UrlSort::Clicks, SortOrder::Desc) => {
+                sqlx::query_as!(
+                    ListedUrlEntity,
+                    r#"
+                    SELECT url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                           url.expires_at, url.max_clicks, url.deleted_at, url.revision,
+                           stats.access_count::bigint AS "access_count!"
+                    FROM url_stats stats
+                    JOIN urls url ON url.id = stats.url_id
+                    WHERE (url.deleted_at IS NOT NULL) = $9
+                      AND ($3::text IS NULL OR url.original_url ILIKE $3)
+                      AND ($4::text IS NULL OR url.short_code LIKE $4)
+                      AND ($5::timestamptz IS NULL OR url.created_at >= $5)
+                      AND ($6::timestamptz IS NULL OR url.created_at < $6)
+                      AND ($7::text IS NULL
+                           OR reverse(url.domain) COLLATE "C" = reverse($7)
+                           OR (reverse(url.domain) COLLATE "C" > reverse($7) || '.'
+                               AND reverse(url.domain) COLLATE "C" < reverse($7) || '/'))
+                      AND ($1::bigint IS NULL OR (stats.access_count, stats.url_id) < ($1, $2::uuid))
+                    ORDER BY stats.access_count DESC, stats.url_id DESC
+                    LIMIT $8
+                    "#,
+                    cursor.map(|cursor| cursor.value()),
+                    cursor.map(|cursor| cursor.id()),
+                    query.search_pattern(),
+                    query.code_prefix_pattern(),
+                    query.from(),
+                    query.to(),
+                    query.domain(),
+                    query.limit() + 1,
+                    query.trashed()
+                )
+                .fetch_all(&self.database.pool)
+                .await?
+            }
+            (UrlSort::Clicks, SortOrder::Asc) => {
+                sqlx::query_as!(
+                    ListedUrlEntity,
+                    r#"
+                    SELECT url.id, url.original_url, url.short_code, url.created_at, url.updated_at,
+                           url.expires_at, url.max_clicks, url.deleted_at, url.revision,
+                           stats.access_count::bigint AS "access_count!"
+                    FROM url_stats stats
+                    JOIN urls url ON url.id = stats.url_id
+                    WHERE (url.deleted_at IS NOT NULL) = $9
+                      AND ($3::text IS NULL OR url.original_url ILIKE $3)
+                      AND ($4::text IS NULL OR url.short_code LIKE $4)
+                      AND ($5::timestamptz IS NULL OR url.created_at >= $5)
+                      AND ($6::timestamptz IS NULL OR url.created_at < $6)
+                      AND ($7::text IS NULL
+                           OR reverse(url.domain) COLLATE "C" = reverse($7)
+                           OR (reverse(url.domain) COLLATE "C" > reverse($7) || '.'
+                               AND reverse(url.domain) COLLATE "C" < reverse($7) || '/'))
+                      AND ($1::bigint IS NULL OR (stats.access_count, stats.url_id) > ($1, $2::uuid))
+                    ORDER BY stats.access_count ASC, stats.url_id ASC
+                    LIMIT $8
+                    "#,
+                    cursor.map(|cursor| cursor.value()),
+                    cursor.map(|cursor| cursor.id()),
+                    query.search_pattern(),
+                    query.code_prefix_pattern(),
+                    query.from(),
+                    query.to(),
+                    query.domain(),
+                    query.limit() + 1,
+                    query.trashed()
+                )
+                .fetch_all(&self.database.pool)
+                .await?
+            }
+        };
+
+        let next_cursor = if rows.len() as i64 > query.limit() {
+            rows.truncate(query.limit() as usize);
+            rows.last().map(|row| {
+                let value = match query.sort() {
+                    UrlSort::CreatedAt => row.created_at.timestamp_micros(),
+                    UrlSort::Clicks => row.access_count,
+                };
+
+                UrlCursor::new(query.sort(), query.order(), value, row.id)
+            })
+        } else {
+            None
+        };
+
+        let urls = rows
+            .into_iter()
+            .map(ListedUrlEntity::to_domain)
+            .collect::<Result<_, UrlError>>()?;
+
+        Ok(UrlPage { urls, next_cursor })
+    }
 }
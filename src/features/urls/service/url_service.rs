@@ -1,17 +1,38 @@
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::configuration::{DedupePolicy, TrashSettings};
 use crate::features::urls::errors::UrlError;
-use crate::features::urls::models::Url;
+use crate::features::urls::generators::{CodeGenerator, ShortCodeGenerator};
+use crate::features::urls::models::{Actor, BatchPlan, NewUrl, Url, UrlPage, UrlRevision};
 use crate::features::urls::repository::IUrlRepository;
-use crate::features::urls::value_objects::{ShortCode, ValidUrl};
+use crate::features::urls::value_objects::{
+    Expiration, ExpirationUpdate, ShortCode, UrlListQuery, ValidUrl,
+};
+use crate::infrastructure::cache::redis_cache::RedisCache;
+use crate::infrastructure::telemetry::ApplicationMetrics;
 
 pub trait IUrlService: Send + Sync {
     fn create_short_url(
         &self,
         original_url: ValidUrl,
         short_code: Option<ShortCode>,
+        expiration: Expiration,
+        actor: Actor,
     ) -> impl Future<Output = Result<Url, UrlError>> + Send;
 
+    /// Creates many links at once, returning a result per item in order. Items
+    /// that failed validation are passed in as errors and reported back as-is.
+    /// With `atomic` nothing is created when any item fails.
+    fn create_short_urls(
+        &self,
+        items: Vec<Result<NewUrl, UrlError>>,
+        atomic: bool,
+        actor: Actor,
+    ) -> impl Future<Output = Result<Vec<Result<Url, UrlError>>, UrlError>> + Send;
+
     fn get_url_by_short_code(
         &self,
         short_code: ShortCode,
@@ -20,25 +41,317 @@ pub trait IUrlService: Send + Sync {
     fn update_url_by_short_code(
         &self,
         short_code: ShortCode,
-        valid_url: ValidUrl,
+        valid_url: Option<ValidUrl>,
+        expiration: ExpirationUpdate,
+        actor: Actor,
+    ) -> impl Future<Output = Result<Url, UrlError>> + Send;
+
+    fn get_url_history(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Vec<UrlRevision>, UrlError>> + Send;
+
+    /// Points a link back to the destination of an earlier revision, recorded
+    /// as a new revision.
+    fn rollback_url_by_short_code(
+        &self,
+        short_code: &ShortCode,
+        revision: i32,
+        actor: Actor,
     ) -> impl Future<Output = Result<Url, UrlError>> + Send;
 
     fn delete_url_by_short_code(
         &self,
         url: &ShortCode,
     ) -> impl Future<Output = Result<bool, UrlError>> + Send;
+
+    fn restore_url_by_short_code(
+        &self,
+        short_code: &ShortCode,
+    ) -> impl Future<Output = Result<Url, UrlError>> + Send;
+
+    fn list_urls(
+        &self,
+        query: UrlListQuery,
+    ) -> impl Future<Output = Result<UrlPage, UrlError>> + Send;
+
+    /// Applies the trash retention policy, returns the number of purged links.
+    fn purge_deleted(&self) -> impl Future<Output = Result<u64, UrlError>> + Send;
 }
 
+pub const MAX_BATCH_SIZE: usize = 10_000;
+
 #[derive(Clone)]
 pub struct UrlService<R: IUrlRepository> {
     url_repo: Arc<R>,
+    cache: Arc<RedisCache>,
+    cache_ttl: Duration,
+    metrics: Arc<ApplicationMetrics>,
+    dedupe: DedupePolicy,
+    trash: TrashSettings,
+    generator: Arc<CodeGenerator>,
 }
 
 impl<R: IUrlRepository> UrlService<R> {
-    pub fn new(url_repository: Arc<R>) -> Self {
+    pub fn new(
+        url_repository: Arc<R>,
+        cache: Arc<RedisCache>,
+        cache_ttl: Duration,
+        metrics: Arc<ApplicationMetrics>,
+        dedupe: DedupePolicy,
+        trash: TrashSettings,
+        generator: Arc<CodeGenerator>,
+    ) -> Self {
         Self {
             url_repo: url_repository,
+            cache,
+            cache_ttl,
+            metrics,
+            dedupe,
+            trash,
+            generator,
+        }
+    }
+
+    fn cache_key(short_code: &ShortCode) -> String {
+        format!("url:{}", short_code.as_str())
+    }
+
+    /// Cache-aside lookup used by the redirect path. Cache failures are logged
+    /// and fall through to the database so Redis is never a hard dependency.
+    async fn find_url_cached(&self, short_code: &ShortCode) -> Result<Url, UrlError> {
+        let key = Self::cache_key(short_code);
+
+        match self.cache.get::<Url>(&key).await {
+            Ok(Some(url)) => return Ok(url),
+            Ok(None) => {}
+            Err(error) => tracing::warn!("Failed to read url from cache: {}", error),
+        }
+
+        let url = self.find_url(short_code).await?;
+        self.cache_url(&url).await;
+
+        Ok(url)
+    }
+
+    async fn cache_url(&self, url: &Url) {
+        // Never keep a link cached past its own expiration date
+        let ttl = match url.expiration.expires_at() {
+            Some(date) => (date - Utc::now())
+                .to_std()
+                .unwrap_or_default()
+                .min(self.cache_ttl),
+            None => self.cache_ttl,
+        };
+
+        if ttl.is_zero() {
+            return;
+        }
+
+        let key = Self::cache_key(&url.short_code);
+
+        if let Err(error) = self.cache.set(&key, url, Some(ttl)).await {
+            tracing::warn!("Failed to write url to cache: {}", error);
+        }
+    }
+
+    async fn invalidate(&self, short_code: &ShortCode) {
+        if let Err(error) = self.cache.delete(&Self::cache_key(short_code)).await {
+            tracing::warn!("Failed to invalidate cached url: {}", error);
+        }
+    }
+
+    async fn find_url(&self, short_code: &ShortCode) -> Result<Url, UrlError> {
+        let result = self.url_repo.find_by_short_code(short_code).await?;
+
+        if let Some(url) = result {
+            Ok(url)
+        } else {
+            let msg = format!(
+                "We're unable to find any url link to this short code: {}",
+                short_code.as_str()
+            );
+            Err(UrlError::NotFound(msg))
+        }
+    }
+
+    /// Inserts a link under a pooled code claimed by the insert itself. Once
+    /// the pool runs dry codes are generated on the spot, retrying with
+    /// another one when the code turns out to be taken.
+    async fn insert_with_generated_code(
+        &self,
+        original_url: ValidUrl,
+        expiration: Expiration,
+        actor: &Actor,
+    ) -> Result<Url, UrlError> {
+        if let Some(pool) = self.generator.pool() {
+            let created_url = self
+                .url_repo
+                .insert_with_pooled_code(&original_url, &expiration, actor)
+                .await?;
+            pool.record_claims(1, usize::from(created_url.is_some()));
+
+            if let Some(created_url) = created_url {
+                return Ok(created_url);
+            }
+        }
+
+        for _ in 0..self.generator.max_attempts() {
+            let url = Url::new(
+                original_url.clone(),
+                self.generator.generate_one().await?,
+                expiration.clone(),
+            );
+
+            if let Some(created_url) = self.url_repo.insert(&url, actor).await? {
+                return Ok(created_url);
+            }
+        }
+
+        tracing::warn!(
+            "Every generated short code was taken after {} attempts, the code space may be running out",
+            self.generator.max_attempts()
+        );
+
+        Err(UrlError::ShortCodesExhausted(String::from(
+            "We currently can't find a unique short code for you, please try again",
+        )))
+    }
+
+    /// Generates short codes for the pending links missing one, regenerating
+    /// those already taken in the batch or in the database.
+    async fn assign_short_codes(
+        &self,
+        pending: &mut [(usize, NewUrl)],
+        claimed: &mut HashSet<String>,
+    ) -> Result<Vec<usize>, UrlError> {
+        let mut unassigned: Vec<usize> = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, item))| item.short_code.is_none())
+            .map(|(position, _)| position)
+            .collect();
+
+        for _ in 0..self.generator.max_attempts() {
+            if unassigned.is_empty() {
+                break;
+            }
+
+            let generated = self.generator.generate(unassigned.len()).await?;
+            let mut candidates = Vec::with_capacity(unassigned.len());
+
+            // Codes already claimed in the batch are left unassigned for the next round
+            for (&position, short_code) in unassigned.iter().zip(generated) {
+                if claimed.insert(short_code.as_str().to_string()) {
+                    candidates.push(short_code.as_str().to_string());
+                    pending[position].1.short_code = Some(short_code);
+                }
+            }
+
+            let taken: HashSet<String> = self
+                .url_repo
+                .find_taken_short_codes(&candidates)
+                .await?
+                .into_iter()
+                .collect();
+
+            unassigned.retain(|&position| {
+                let short_code = &mut pending[position].1.short_code;
+                short_code.take_if(|short_code| taken.contains(short_code.as_str()));
+                short_code.is_none()
+            });
+        }
+
+        Ok(unassigned)
+    }
+
+    /// Short codes generated for the pending links at `positions`.
+    fn generated_codes(pending: &[(usize, NewUrl)], positions: &[usize]) -> Vec<String> {
+        positions
+            .iter()
+            .filter_map(|&position| pending[position].1.short_code.as_ref())
+            .map(|short_code| short_code.as_str().to_string())
+            .collect()
+    }
+
+    /// Hands the codes generated for a batch that created nothing to the pool,
+    /// pooled ones were already claimed and would otherwise be lost.
+    async fn release_short_codes(&self, short_codes: Vec<String>) {
+        let Some(pool) = self.generator.pool() else {
+            return;
+        };
+
+        if short_codes.is_empty() {
+            return;
+        }
+
+        if let Err(error) = pool.release(&short_codes).await {
+            tracing::warn!(
+                "Failed to return {} short codes to the pool: {}",
+                short_codes.len(),
+                error
+            );
+        }
+    }
+
+    /// Like `find_url`, but a link in the trash is gone and can't be changed.
+    async fn find_live_url(&self, short_code: &ShortCode) -> Result<Url, UrlError> {
+        let url = self.find_url(short_code).await?;
+
+        if url.is_deleted() {
+            return Err(Self::deleted(&url));
         }
+
+        Ok(url)
+    }
+
+    fn missing_id(url: &Url) -> UrlError {
+        UrlError::NotFound(format!(
+            "The short code '{}' has not been saved",
+            url.short_code.as_str()
+        ))
+    }
+
+    fn taken_concurrently(url: &Url) -> UrlError {
+        UrlError::Duplicate(format!(
+            "The short code '{}' was taken concurrently, please try again",
+            url.short_code.as_str()
+        ))
+    }
+
+    fn deleted(url: &Url) -> UrlError {
+        UrlError::Expired(format!(
+            "The short code '{}' has been deleted",
+            url.short_code.as_str()
+        ))
+    }
+
+    /// Rejects deleted links, and those whose expiration date has passed or
+    /// whose click quota is used up.
+    async fn ensure_available(&self, url: &Url) -> Result<(), UrlError> {
+        if url.is_deleted() {
+            return Err(Self::deleted(url));
+        }
+
+        if url.expiration.is_expired_at(Utc::now()) {
+            return Err(UrlError::Expired(format!(
+                "The short code '{}' has expired",
+                url.short_code.as_str()
+            )));
+        }
+
+        if let (Some(_), Some(url_id)) = (url.expiration.max_clicks(), url.id) {
+            let clicks = self.url_repo.count_clicks(url_id).await?;
+
+            if url.expiration.is_exhausted_by(clicks) {
+                return Err(UrlError::Expired(format!(
+                    "The short code '{}' has reached its click limit",
+                    url.short_code.as_str()
+                )));
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -47,69 +360,272 @@ impl<R: IUrlRepository> IUrlService for UrlService<R> {
         &self,
         original_url: ValidUrl,
         short_code: Option<ShortCode>,
+        expiration: Expiration,
+        actor: Actor,
     ) -> Result<Url, UrlError> {
-        if let Some(existing) = self.url_repo.find_by_original_url(&original_url).await? {
+        if self.dedupe.reuses(short_code.is_some())
+            && let Some(existing) = self
+                .url_repo
+                .find_by_original_url(&original_url, &expiration)
+                .await?
+        {
             return Ok(existing);
         }
 
-        let mut short_code = match short_code {
-            Some(value) => value,
-            None => ShortCode::new(None)?,
+        let created_url = match short_code {
+            Some(short_code) => {
+                let url = Url::new(original_url, short_code, expiration);
+
+                self.url_repo
+                    .insert(&url, &actor)
+                    .await?
+                    .ok_or_else(|| UrlError::code_in_use(&url.short_code))?
+            }
+            None => {
+                self.insert_with_generated_code(original_url, expiration, &actor)
+                    .await?
+            }
         };
+        // A purged link may have left its code cached
+        self.invalidate(&created_url.short_code).await;
+        self.metrics.increment_url_creation();
 
-        let mut attempts = 0;
+        Ok(created_url)
+    }
+
+    async fn create_short_urls(
+        &self,
+        items: Vec<Result<NewUrl, UrlError>>,
+        atomic: bool,
+        actor: Actor,
+    ) -> Result<Vec<Result<Url, UrlError>>, UrlError> {
+        if items.is_empty() || items.len() > MAX_BATCH_SIZE {
+            return Err(UrlError::InvalidBatch(format!(
+                "A batch must hold between 1 and {MAX_BATCH_SIZE} links"
+            )));
+        }
 
-        while self.url_repo.exists_by_short_code(&short_code).await? {
-            if attempts >= 5 {
-                return Err(UrlError::Duplicate(String::from(
+        let destinations: Vec<(String, Expiration)> = items
+            .iter()
+            .flatten()
+            .map(|item| {
+                (
+                    item.original_url.as_str().to_string(),
+                    item.expiration.clone(),
+                )
+            })
+            .collect();
+        let custom_codes: Vec<String> = items
+            .iter()
+            .flatten()
+            .filter_map(|item| item.short_code.as_ref())
+            .map(|short_code| short_code.as_str().to_string())
+            .collect();
+
+        let existing = self.url_repo.find_by_original_urls(&destinations).await?;
+        let taken: HashSet<String> = self
+            .url_repo
+            .find_taken_short_codes(&custom_codes)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut plan = BatchPlan::new(items, existing, &taken, self.dedupe);
+        let generating: Vec<usize> = plan
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, item))| item.short_code.is_none())
+            .map(|(position, _)| position)
+            .collect();
+
+        let unassigned = match self
+            .assign_short_codes(&mut plan.pending, &mut plan.claimed)
+            .await
+        {
+            Ok(unassigned) => unassigned,
+            Err(error) => {
+                self.release_short_codes(Self::generated_codes(&plan.pending, &generating))
+                    .await;
+                return Err(error);
+            }
+        };
+        let generated = Self::generated_codes(&plan.pending, &generating);
+
+        for position in unassigned {
+            let index = plan.pending[position].0;
+            plan.answer(
+                index,
+                Err(UrlError::ShortCodesExhausted(String::from(
                     "We currently can't find a unique short code for you, please try again",
-                )));
+                ))),
+            );
+        }
+
+        if atomic && plan.has_failures() {
+            self.release_short_codes(generated).await;
+            return Ok(plan.abort());
+        }
+
+        // Links left without a short code were already reported as failed
+        let (indexes, urls): (Vec<usize>, Vec<Url>) = std::mem::take(&mut plan.pending)
+            .into_iter()
+            .filter_map(|(index, item)| {
+                let short_code = item.short_code?;
+                Some((
+                    index,
+                    Url::new(item.original_url, short_code, item.expiration),
+                ))
+            })
+            .unzip();
+
+        let inserted = match self.url_repo.insert_many(&urls, atomic, &actor).await {
+            Ok(inserted) => inserted,
+            Err(error) => {
+                self.release_short_codes(generated).await;
+                return Err(error);
+            }
+        };
+        let mut inserted: HashMap<String, Url> = inserted
+            .into_iter()
+            .map(|url| (url.short_code.as_str().to_string(), url))
+            .collect();
+        self.metrics
+            .increment_url_creation_by(inserted.len() as u64);
+
+        // An atomic batch is rolled back when a code was taken concurrently, the
+        // items whose code now exists are the ones that failed
+        if atomic && inserted.len() < urls.len() {
+            let short_codes: Vec<String> = urls
+                .iter()
+                .map(|url| url.short_code.as_str().to_string())
+                .collect();
+            let taken: HashSet<String> = self
+                .url_repo
+                .find_taken_short_codes(&short_codes)
+                .await?
+                .into_iter()
+                .collect();
+
+            for (index, url) in indexes.into_iter().zip(urls) {
+                if taken.contains(url.short_code.as_str()) {
+                    plan.answer(index, Err(Self::taken_concurrently(&url)));
+                }
             }
 
-            short_code = ShortCode::new(None)?;
-            attempts += 1;
+            self.release_short_codes(generated).await;
+            return Ok(plan.abort());
         }
 
-        let url = Url::new(original_url, short_code);
-        let created_url = self.url_repo.save(&url).await?;
+        for (index, url) in indexes.into_iter().zip(urls) {
+            plan.answer(
+                index,
+                inserted
+                    .remove(url.short_code.as_str())
+                    .ok_or_else(|| Self::taken_concurrently(&url)),
+            );
+        }
 
-        Ok(created_url)
+        Ok(plan.finish())
     }
 
     async fn get_url_by_short_code(&self, short_code: ShortCode) -> Result<Url, UrlError> {
-        let result = self.url_repo.find_by_short_code(&short_code).await?;
+        let url = self.find_url_cached(&short_code).await?;
+        self.ensure_available(&url).await?;
 
-        if let Some(url) = result {
-            Ok(url)
-        } else {
-            let msg = format!(
-                "We're unable to find any url link to this short code: {}",
-                short_code.as_str()
-            );
-            Err(UrlError::NotFound(msg))
-        }
+        Ok(url)
     }
 
     async fn update_url_by_short_code(
         &self,
         short_code: ShortCode,
-        valid_url: ValidUrl,
+        valid_url: Option<ValidUrl>,
+        expiration: ExpirationUpdate,
+        actor: Actor,
     ) -> Result<Url, UrlError> {
-        let mut url = self.get_url_by_short_code(short_code).await?;
+        let mut url = self.find_live_url(&short_code).await?;
+
+        if let Some(valid_url) = valid_url {
+            url.update_url(valid_url);
+        }
+        url.update_expiration(expiration);
+
+        let updated_url = self.url_repo.update(&url, &actor, None).await?;
+        self.invalidate(&short_code).await;
 
-        self.url_repo.update(&mut url, valid_url).await
+        Ok(updated_url)
+    }
+
+    async fn get_url_history(&self, short_code: &ShortCode) -> Result<Vec<UrlRevision>, UrlError> {
+        let url = self.find_url(short_code).await?;
+        let url_id = url.id.ok_or_else(|| Self::missing_id(&url))?;
+
+        self.url_repo.list_revisions(url_id).await
+    }
+
+    async fn rollback_url_by_short_code(
+        &self,
+        short_code: &ShortCode,
+        revision: i32,
+        actor: Actor,
+    ) -> Result<Url, UrlError> {
+        let mut url = self.find_live_url(short_code).await?;
+        let url_id = url.id.ok_or_else(|| Self::missing_id(&url))?;
+
+        let Some(target) = self.url_repo.find_revision(url_id, revision).await? else {
+            return Err(UrlError::NotFound(format!(
+                "The short code '{}' has no revision {}",
+                short_code.as_str(),
+                revision
+            )));
+        };
+
+        url.update_url(ValidUrl::new(target.original_url)?);
+
+        let updated_url = self
+            .url_repo
+            .update(&url, &actor, Some(target.revision))
+            .await?;
+        self.invalidate(short_code).await;
+
+        Ok(updated_url)
     }
 
     async fn delete_url_by_short_code(&self, short_code: &ShortCode) -> Result<bool, UrlError> {
-        let url = self.url_repo.find_by_short_code(short_code).await?;
+        self.find_live_url(short_code).await?;
 
-        if url.is_none() {
+        let deleted = self.url_repo.delete_by_short_code(short_code).await?;
+        self.invalidate(short_code).await;
+
+        Ok(deleted)
+    }
+
+    async fn restore_url_by_short_code(&self, short_code: &ShortCode) -> Result<Url, UrlError> {
+        let restored = self.url_repo.restore_by_short_code(short_code).await?;
+
+        let Some(url) = restored else {
             return Err(UrlError::NotFound(format!(
-                "URL with the associated shortcode: '{}' is not found!",
+                "There is no deleted link with the short code '{}'",
                 short_code.as_str()
             )));
-        }
+        };
+        self.invalidate(short_code).await;
+
+        Ok(url)
+    }
+
+    async fn list_urls(&self, query: UrlListQuery) -> Result<UrlPage, UrlError> {
+        self.url_repo.list(&query).await
+    }
 
-        self.url_repo.delete_by_short_code(short_code).await
+    async fn purge_deleted(&self) -> Result<u64, UrlError> {
+        match self.trash.retention() {
+            Some(retention) => {
+                self.url_repo
+                    .purge_deleted_before(Utc::now() - retention)
+                    .await
+            }
+            None => Ok(0),
+        }
     }
 }
@@ -0,0 +1,152 @@
+use chrono::{DateTime, SubsecRound, Utc};
+
+use crate::features::urls::errors::UrlError;
+
+/// Optional lifetime of a short link, bounded by a date and/or a click quota.
+/// Dates keep the microsecond precision Postgres stores, so a requested
+/// expiration compares equal to the stored one.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Expiration {
+    expires_at: Option<DateTime<Utc>>,
+    max_clicks: Option<i32>,
+}
+
+impl Expiration {
+    pub fn new(
+        expires_at: Option<DateTime<Utc>>,
+        max_clicks: Option<i32>,
+    ) -> Result<Self, UrlError> {
+        if expires_at.is_some_and(|date| date <= Utc::now()) {
+            return Err(UrlError::InvalidExpiration(
+                "Expiration date must be in the future".into(),
+            ));
+        }
+
+        if max_clicks.is_some_and(|clicks| clicks < 1) {
+            return Err(UrlError::InvalidExpiration(
+                "Max clicks must be greater than zero".into(),
+            ));
+        }
+
+        Ok(Self {
+            expires_at: expires_at.map(|date| date.trunc_subsecs(6)),
+            max_clicks,
+        })
+    }
+
+    /// Rebuilds an expiration from persisted values without re-validating them,
+    /// a stored date is allowed to be in the past.
+    pub fn from_parts(expires_at: Option<DateTime<Utc>>, max_clicks: Option<i32>) -> Self {
+        Self {
+            expires_at,
+            max_clicks,
+        }
+    }
+
+    /// Applies the fields set on `update`, keeping the rest.
+    pub fn merge(&mut self, update: ExpirationUpdate) {
+        if let Some(expires_at) = update.expires_at {
+            self.expires_at = expires_at;
+        }
+
+        if let Some(max_clicks) = update.max_clicks {
+            self.max_clicks = max_clicks;
+        }
+    }
+
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.expires_at
+    }
+
+    pub fn max_clicks(&self) -> Option<i32> {
+        self.max_clicks
+    }
+
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|date| date <= now)
+    }
+
+    pub fn is_exhausted_by(&self, clicks: i64) -> bool {
+        self.max_clicks
+            .is_some_and(|quota| clicks >= i64::from(quota))
+    }
+}
+
+/// Changes to an expiration, where `None` keeps a field and `Some(None)` removes the limit.
+#[derive(Debug, Clone, Default)]
+pub struct ExpirationUpdate {
+    expires_at: Option<Option<DateTime<Utc>>>,
+    max_clicks: Option<Option<i32>>,
+}
+
+impl ExpirationUpdate {
+    pub fn new(
+        expires_at: Option<Option<DateTime<Utc>>>,
+        max_clicks: Option<Option<i32>>,
+    ) -> Result<Self, UrlError> {
+        Expiration::new(expires_at.flatten(), max_clicks.flatten())?;
+
+        Ok(Self {
+            expires_at: expires_at.map(|date| date.map(|date| date.trunc_subsecs(6))),
+            max_clicks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn rejects_dates_in_the_past_and_non_positive_quotas() {
+        let yesterday = Utc::now() - Duration::days(1);
+
+        assert!(Expiration::new(Some(yesterday), None).is_err());
+        assert!(Expiration::new(None, Some(0)).is_err());
+        assert!(Expiration::new(None, Some(-3)).is_err());
+        assert!(Expiration::new(None, None).is_ok());
+    }
+
+    #[test]
+    fn detects_expired_and_exhausted_links() {
+        let now = Utc::now();
+        let expiration = Expiration::from_parts(Some(now - Duration::minutes(1)), Some(10));
+
+        assert!(expiration.is_expired_at(now));
+        assert!(!expiration.is_exhausted_by(9));
+        assert!(expiration.is_exhausted_by(10));
+        assert!(!Expiration::default().is_expired_at(now));
+        assert!(!Expiration::default().is_exhausted_by(i64::MAX));
+    }
+
+    #[test]
+    fn merge_only_overrides_supplied_fields() {
+        let tomorrow = Utc::now() + Duration::days(1);
+        let mut expiration = Expiration::from_parts(Some(tomorrow), Some(5));
+
+        expiration.merge(ExpirationUpdate::new(None, Some(Some(50))).unwrap());
+
+        assert_eq!(expiration.expires_at(), Some(tomorrow));
+        assert_eq!(expiration.max_clicks(), Some(50));
+    }
+
+    #[test]
+    fn merge_clears_explicitly_removed_limits() {
+        let tomorrow = Utc::now() + Duration::days(1);
+        let mut expiration = Expiration::from_parts(Some(tomorrow), Some(5));
+
+        expiration.merge(ExpirationUpdate::new(Some(None), Some(None)).unwrap());
+
+        assert_eq!(expiration, Expiration::default());
+    }
+
+    #[test]
+    fn update_rejects_invalid_limits() {
+        let yesterday = Utc::now() - Duration::days(1);
+
+        assert!(ExpirationUpdate::new(Some(Some(yesterday)), None).is_err());
+        assert!(ExpirationUpdate::new(None, Some(Some(0))).is_err());
+    }
+}
@@ -1,5 +1,9 @@
+mod expiration;
 mod short_code;
+mod url_list_query;
 mod valid_url;
 
+pub use expiration::*;
 pub use short_code::*;
+pub use url_list_query::*;
 pub use valid_url::*;
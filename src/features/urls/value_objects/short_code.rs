@@ -1,34 +1,23 @@
-use base64::{Engine as _, engine::general_purpose};
-use rand::Rng;
-
 use crate::features::urls::errors::UrlError;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct ShortCode(String);
 
 impl ShortCode {
-    pub fn new(custom_code: Option<String>) -> Result<Self, UrlError> {
-        let short_code = if let Some(custom_value) = custom_code {
-            let min = 3;
-            let max = 8;
-
-            if custom_value.len() < min || custom_value.len() > max {
-                return Err(UrlError::InvalidShortCode(format!(
-                    "Custom code can not be less than {min} or greater than {max}",
-                )));
-            }
+    pub const MIN_LENGTH: usize = 3;
+    pub const MAX_LENGTH: usize = 16;
 
-            Self(custom_value)
-        } else {
-            let mut rng = rand::rng();
-            let random_bytes: [u8; 6] = rng.random();
+    /// Wraps a custom or generated code, new ones come from a `ShortCodeGenerator`.
+    pub fn new(code: String) -> Result<Self, UrlError> {
+        let (min, max) = (Self::MIN_LENGTH, Self::MAX_LENGTH);
 
-            // Use URL-safe Base64 encoding and take only first 8 chars
-            let gen_bytes = general_purpose::URL_SAFE_NO_PAD.encode(&random_bytes);
-            Self(gen_bytes[0..8].to_string())
-        };
+        if code.len() < min || code.len() > max {
+            return Err(UrlError::InvalidShortCode(format!(
+                "Short code must be {min} to {max} characters long",
+            )));
+        }
 
-        Ok(short_code)
+        Ok(Self(code))
     }
 
     pub fn as_str(&self) -> &str {
@@ -44,23 +33,23 @@ impl ShortCode {
 mod test {
     use super::ShortCode;
 
-    #[test]
-    fn generate_short_code_when_one_is_not_supplied() {
-        let short_code = ShortCode::new(None);
-        assert!(short_code.is_ok());
-    }
-
     #[test]
     fn convert_custom_code_into_short_code() {
         // Given
         let custom_code = "brilliant_coder";
 
         // When
-        let result = ShortCode::new(Some(custom_code.into()));
+        let result = ShortCode::new(custom_code.into());
 
         // Then
         assert!(result.is_ok());
         let short_code = result.unwrap();
         assert_eq!(short_code.as_str(), custom_code);
     }
+
+    #[test]
+    fn reject_custom_code_out_of_bounds() {
+        assert!(ShortCode::new("ab".into()).is_err());
+        assert!(ShortCode::new("a".repeat(17)).is_err());
+    }
 }
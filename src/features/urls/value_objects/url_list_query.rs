@@ -0,0 +1,325 @@
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::features::urls::errors::UrlError;
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlSort {
+    #[default]
+    CreatedAt,
+    Clicks,
+}
+
+impl UrlSort {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::Clicks => "clicks",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "asc",
+            Self::Desc => "desc",
+        }
+    }
+}
+
+/// Opaque keyset position: the sort value and id of the last link returned.
+/// Creation times are kept as microseconds, the precision Postgres stores.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UrlCursor {
+    sort: UrlSort,
+    order: SortOrder,
+    value: i64,
+    id: Uuid,
+}
+
+impl UrlCursor {
+    pub fn new(sort: UrlSort, order: SortOrder, value: i64, id: Uuid) -> Self {
+        Self {
+            sort,
+            order,
+            value,
+            id,
+        }
+    }
+
+    pub fn decode(value: &str) -> Result<Self, UrlError> {
+        let invalid = || UrlError::InvalidQuery("Invalid cursor".into());
+
+        let bytes = general_purpose::URL_SAFE_NO_PAD
+            .decode(value)
+            .map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        let mut parts = raw.splitn(4, '|');
+        let (Some(sort), Some(order), Some(value), Some(id)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+
+        Ok(Self {
+            sort: match sort {
+                "created_at" => UrlSort::CreatedAt,
+                "clicks" => UrlSort::Clicks,
+                _ => return Err(invalid()),
+            },
+            order: match order {
+                "asc" => SortOrder::Asc,
+                "desc" => SortOrder::Desc,
+                _ => return Err(invalid()),
+            },
+            value: value.parse().map_err(|_| invalid())?,
+            id: Uuid::parse_str(id).map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}|{}|{}",
+            self.sort.as_str(),
+            self.order.as_str(),
+            self.value,
+            self.id
+        );
+        general_purpose::URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+}
+
+/// Validated filters, sorting and page position for listing links.
+#[derive(Debug, Clone)]
+pub struct UrlListQuery {
+    limit: i64,
+    cursor: Option<UrlCursor>,
+    sort: UrlSort,
+    order: SortOrder,
+    domain: Option<String>,
+    search: Option<String>,
+    code_prefix: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    trashed: bool,
+}
+
+impl UrlListQuery {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        limit: Option<i64>,
+        cursor: Option<String>,
+        sort: Option<UrlSort>,
+        order: Option<SortOrder>,
+        domain: Option<String>,
+        search: Option<String>,
+        code_prefix: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<Self, UrlError> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT);
+        let sort = sort.unwrap_or_default();
+        let order = order.unwrap_or_default();
+
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(UrlError::InvalidQuery(format!(
+                "`limit` must be between 1 and {MAX_LIMIT}"
+            )));
+        }
+
+        if let (Some(from), Some(to)) = (from, to)
+            && from >= to
+        {
+            return Err(UrlError::InvalidQuery(
+                "`from` must be earlier than `to`".into(),
+            ));
+        }
+
+        let cursor = cursor.as_deref().map(UrlCursor::decode).transpose()?;
+
+        // A cursor only makes sense for the ordering that produced it
+        if cursor.is_some_and(|cursor| cursor.sort != sort || cursor.order != order) {
+            return Err(UrlError::InvalidQuery(
+                "Cursor does not match the requested sort".into(),
+            ));
+        }
+
+        let domain = domain
+            .map(|domain| domain.trim().to_lowercase())
+            .map(|domain| domain.trim_start_matches("www.").to_string())
+            .filter(|domain| !domain.is_empty());
+
+        Ok(Self {
+            limit,
+            cursor,
+            sort,
+            order,
+            domain,
+            search: search.filter(|search| !search.is_empty()),
+            code_prefix: code_prefix.filter(|prefix| !prefix.is_empty()),
+            from,
+            to,
+            trashed: false,
+        })
+    }
+
+    /// Lists deleted links instead of live ones.
+    pub fn in_trash(mut self) -> Self {
+        self.trashed = true;
+        self
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.limit
+    }
+
+    pub fn cursor(&self) -> Option<UrlCursor> {
+        self.cursor
+    }
+
+    pub fn sort(&self) -> UrlSort {
+        self.sort
+    }
+
+    pub fn order(&self) -> SortOrder {
+        self.order
+    }
+
+    /// Destination host to match, subdomains included, without a leading `www.`.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn from(&self) -> Option<DateTime<Utc>> {
+        self.from
+    }
+
+    pub fn to(&self) -> Option<DateTime<Utc>> {
+        self.to
+    }
+
+    pub fn trashed(&self) -> bool {
+        self.trashed
+    }
+
+    /// `ILIKE` pattern matching the search term anywhere in the destination.
+    pub fn search_pattern(&self) -> Option<String> {
+        self.search
+            .as_deref()
+            .map(|search| format!("%{}%", Self::escape(search)))
+    }
+
+    /// `LIKE` pattern matching short codes starting with the prefix.
+    pub fn code_prefix_pattern(&self) -> Option<String> {
+        self.code_prefix
+            .as_deref()
+            .map(|prefix| format!("{}%", Self::escape(prefix)))
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn query(cursor: Option<String>, sort: Option<UrlSort>) -> Result<UrlListQuery, UrlError> {
+        UrlListQuery::new(None, cursor, sort, None, None, None, None, None, None)
+    }
+
+    #[test]
+    fn cursor_round_trips_and_must_match_the_sort() {
+        let cursor = UrlCursor::new(UrlSort::Clicks, SortOrder::Desc, 42, Uuid::new_v4());
+
+        assert_eq!(UrlCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(UrlCursor::decode("garbage").is_err());
+        assert!(query(Some(cursor.encode()), Some(UrlSort::Clicks)).is_ok());
+        assert!(query(Some(cursor.encode()), None).is_err());
+    }
+
+    #[test]
+    fn validates_limit_and_creation_range() {
+        let now = Utc::now();
+
+        assert_eq!(query(None, None).unwrap().limit(), DEFAULT_LIMIT);
+        assert!(
+            UrlListQuery::new(
+                Some(MAX_LIMIT + 1),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None
+            )
+            .is_err()
+        );
+        assert!(
+            UrlListQuery::new(
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(now),
+                Some(now - Duration::days(1))
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn filters_are_normalized_and_escaped() {
+        let query = UrlListQuery::new(
+            None,
+            None,
+            None,
+            None,
+            Some(" WWW.Example.com ".into()),
+            Some("50%_off".into()),
+            Some("ab_".into()),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(query.domain(), Some("example.com"));
+        assert_eq!(query.search_pattern().as_deref(), Some("%50\\%\\_off%"));
+        assert_eq!(query.code_prefix_pattern().as_deref(), Some("ab\\_%"));
+    }
+}
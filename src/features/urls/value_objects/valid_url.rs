@@ -25,12 +25,16 @@ impl ValidUrl {
                     return Err(UrlError::InvalidUrl("Missing host name".to_string()));
                 }
 
-                match parsed.host_str() {
-                    Some(domain) => {
-                        Self::validate_domain(domain)?;
-                        Self::validate_tld(domain)?;
-                    }
-                    _ => {}
+                // The parser turns unicode hosts into punycode, check them as written
+                if !Self::raw_host(&url).is_ascii() {
+                    return Err(UrlError::InvalidUrl(
+                        "Domain parts can only contain letters, numbers, and hyphens".into(),
+                    ));
+                }
+
+                if let Some(domain) = parsed.host_str() {
+                    Self::validate_domain(domain)?;
+                    Self::validate_tld(domain)?;
                 }
 
                 Ok(Self(url))
@@ -45,6 +49,12 @@ impl ValidUrl {
         }
     }
 
+    fn raw_host(url: &str) -> &str {
+        url.split_once("://")
+            .and_then(|(_, rest)| rest.split(['/', '?', '#']).next())
+            .unwrap_or_default()
+    }
+
     fn validate_domain(domain: &str) -> Result<(), UrlError> {
         if !domain.contains(".") {
             return Err(UrlError::InvalidUrl(
@@ -84,7 +94,7 @@ impl ValidUrl {
     fn validate_tld(domain: &str) -> Result<(), UrlError> {
         let tld = domain
             .split('.')
-            .last()
+            .next_back()
             .ok_or_else(|| UrlError::InvalidUrl("Missing TLD".into()))?;
 
         if !VALID_TLDS.contains(&tld) {
@@ -109,9 +119,9 @@ impl AsRef<str> for ValidUrl {
     }
 }
 
-impl Into<String> for ValidUrl {
-    fn into(self) -> String {
-        self.0
+impl From<ValidUrl> for String {
+    fn from(value: ValidUrl) -> Self {
+        value.0
     }
 }
 
@@ -128,6 +138,8 @@ mod tests {
             "http://example.org",
             "https://sub.domain.co.uk",
             "http://test.io",
+            "https://xn--gogle-jua.com",
+            "https://example.com/café",
         ];
 
         for url in valid_urls {
@@ -1,22 +1,55 @@
-use redis::{AsyncCommands, Client};
+use std::time::Duration;
 
-use crate::infrastructure::cache::error::CacheError;
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+
+use crate::infrastructure::{cache::error::CacheError, securtity::RateLimitDecision};
 
 pub struct RateLimitCache {
-    client: Client,
+    connection: ConnectionManager,
 }
 
 impl RateLimitCache {
-    pub async fn increment_request(&self, ip: &str) -> Result<u32, CacheError> {
-        let mut connection = self.client.get_multiplexed_async_connection().await?;
-        let key = format!("rate_limit:{}", ip);
+    pub async fn new(redis_url: &str) -> Result<Self, CacheError> {
+        let connection = ConnectionManager::new(Client::open(redis_url)?).await?;
+
+        Ok(Self { connection })
+    }
+
+    /// Fixed-window counter shared by every instance of the service.
+    pub async fn increment_request(
+        &self,
+        client_id: &str,
+        limit: u32,
+        window: Duration,
+    ) -> Result<RateLimitDecision, CacheError> {
+        let mut connection = self.connection.clone();
+        let key = format!("rate_limit:{}", client_id);
 
-        let current_count: u32 = connection.incr(&key, 1).await?;
+        let (current_count, ttl): (u32, i64) = redis::pipe()
+            .atomic()
+            .cmd("SET")
+            .arg(&key)
+            .arg(0)
+            .arg("EX")
+            .arg(window.as_secs().max(1))
+            .arg("NX")
+            .ignore()
+            .incr(&key, 1)
+            .ttl(&key)
+            .query_async(&mut connection)
+            .await?;
 
-        if current_count == 1 {
-            connection.expire::<&str, ()>(&key, 60).await?; // 1-minute window
+        if ttl < 0 {
+            connection
+                .expire::<&str, ()>(&key, window.as_secs().max(1) as i64)
+                .await?;
         }
 
-        Ok(current_count)
+        Ok(RateLimitDecision {
+            allowed: current_count <= limit,
+            limit,
+            remaining: limit.saturating_sub(current_count),
+            reset_after: Duration::from_secs(ttl.max(0) as u64),
+        })
     }
 }
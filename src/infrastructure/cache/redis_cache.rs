@@ -1,11 +1,12 @@
 use super::error::CacheError;
-use redis::{AsyncCommands, Client, ConnectionLike};
+use redis::{AsyncCommands, Client, Pipeline, aio::ConnectionManager};
 use serde::{Serialize, de::DeserializeOwned};
 use std::time::Duration;
 
-#[derive(Debug)]
+/// Commands share one multiplexed connection, re-established in the
+/// background when it drops.
 pub struct RedisCache {
-    client: Client,
+    connection: ConnectionManager,
 }
 
 impl RedisCache {
@@ -14,22 +15,20 @@ impl RedisCache {
         skip(redis_url),
         fields(is_redis_connected=tracing::field::Empty)
     )]
-    pub fn new(redis_url: &str) -> Result<Self, CacheError> {
-        let mut client = Client::open(redis_url)?;
+    pub async fn new(redis_url: &str) -> Result<Self, CacheError> {
+        let client = Client::open(redis_url)?;
 
-        let is_redis_connected = client.check_connection();
+        let connection = match ConnectionManager::new(client).await {
+            Ok(connection) => connection,
+            Err(error) => {
+                tracing::error!("{}: {}", CacheError::RedisConnectionError, error);
+                return Err(CacheError::RedisConnectionError);
+            }
+        };
 
-        if !is_redis_connected {
-            tracing::error!("{}", CacheError::RedisConnectionError);
-            return Err(CacheError::RedisConnectionError);
-        }
-
-        tracing::Span::current().record(
-            "is_redis_connected",
-            tracing::field::display(is_redis_connected),
-        );
+        tracing::Span::current().record("is_redis_connected", tracing::field::display(true));
 
-        Ok(RedisCache { client })
+        Ok(RedisCache { connection })
     }
 
     pub async fn set<T: Serialize>(
@@ -38,7 +37,7 @@ impl RedisCache {
         value: &T,
         expiration: Option<Duration>,
     ) -> Result<(), CacheError> {
-        let mut connect = self.client.get_multiplexed_async_connection().await?;
+        let mut connect = self.connection.clone();
         let serialized = serde_json::to_string(value)?;
 
         match expiration {
@@ -50,7 +49,7 @@ impl RedisCache {
     }
 
     pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, CacheError> {
-        let mut connection = self.client.get_multiplexed_async_connection().await?;
+        let mut connection = self.connection.clone();
         let result: Option<String> = connection.get(key).await?;
 
         let deserialized = result
@@ -58,4 +57,107 @@ impl RedisCache {
 
         deserialized.transpose()
     }
+
+    pub async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        let mut connection = self.connection.clone();
+        connection.del::<&str, ()>(key).await?;
+
+        Ok(())
+    }
+
+    /// Adds elements to HyperLogLog sketches in a single round trip, each
+    /// sketch getting `expiration` (re)applied.
+    pub async fn pf_add(
+        &self,
+        sketches: &[(String, Vec<String>)],
+        expiration: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        if sketches.is_empty() {
+            return Ok(());
+        }
+
+        let mut pipeline = Pipeline::new();
+
+        for (key, elements) in sketches {
+            pipeline.pfadd(key, elements).ignore();
+
+            if let Some(duration) = expiration {
+                pipeline
+                    .expire(key, duration.as_secs().try_into().unwrap_or(i64::MAX))
+                    .ignore();
+            }
+        }
+
+        let mut connection = self.connection.clone();
+        pipeline.query_async::<()>(&mut connection).await?;
+
+        Ok(())
+    }
+
+    /// Replaces the content of a sorted set in a single round trip.
+    pub async fn replace_sorted_set(
+        &self,
+        key: &str,
+        members: &[(String, i64)],
+        expiration: Duration,
+    ) -> Result<(), CacheError> {
+        let scored: Vec<(i64, &str)> = members
+            .iter()
+            .map(|(member, score)| (*score, member.as_str()))
+            .collect();
+
+        let mut pipeline = Pipeline::new();
+        pipeline.del(key).ignore();
+
+        if !scored.is_empty() {
+            pipeline.zadd_multiple(key, &scored).ignore();
+            pipeline
+                .expire(key, expiration.as_secs().try_into().unwrap_or(i64::MAX))
+                .ignore();
+        }
+
+        let mut connection = self.connection.clone();
+        pipeline.query_async::<()>(&mut connection).await?;
+
+        Ok(())
+    }
+
+    /// Highest scored members of a sorted set, best first.
+    pub async fn sorted_set_top(
+        &self,
+        key: &str,
+        count: usize,
+    ) -> Result<Vec<(String, i64)>, CacheError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut connection = self.connection.clone();
+        let members: Vec<(String, f64)> = connection
+            .zrevrange_withscores(key, 0, count as isize - 1)
+            .await?;
+
+        Ok(members
+            .into_iter()
+            .map(|(member, score)| (member, score as i64))
+            .collect())
+    }
+
+    /// Estimated cardinality of the union of each group of sketches, in order.
+    /// Missing keys count as empty sketches.
+    pub async fn pf_count(&self, groups: &[Vec<String>]) -> Result<Vec<u64>, CacheError> {
+        if groups.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipeline = Pipeline::new();
+
+        for keys in groups {
+            pipeline.pfcount(keys);
+        }
+
+        let mut connection = self.connection.clone();
+
+        Ok(pipeline.query_async(&mut connection).await?)
+    }
 }
@@ -0,0 +1,5 @@
+#[derive(thiserror::Error, Debug)]
+pub enum GeoIpError {
+    #[error("Failed to open GeoIP database: {0}")]
+    Database(#[from] maxminddb::MaxMindDbError),
+}
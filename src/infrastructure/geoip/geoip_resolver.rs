@@ -0,0 +1,54 @@
+use std::net::IpAddr;
+
+use maxminddb::{Reader, geoip2};
+
+use crate::{
+    features::url_stats::value_objects::GeoLocation, infrastructure::geoip::error::GeoIpError,
+};
+
+/// Resolves IP addresses against a local MaxMind City database (GeoLite2 or GeoIP2).
+pub struct GeoIpResolver {
+    reader: Reader<Vec<u8>>,
+}
+
+impl GeoIpResolver {
+    pub fn open(path: &str) -> Result<Self, GeoIpError> {
+        Ok(Self {
+            reader: Reader::open_readfile(path)?,
+        })
+    }
+
+    /// Returns `None` for unparsable or private addresses and for addresses
+    /// missing from the database.
+    pub fn lookup(&self, ip_address: &str) -> Option<GeoLocation> {
+        let ip: IpAddr = ip_address
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse()
+            .ok()?;
+
+        let city = match self
+            .reader
+            .lookup(ip)
+            .and_then(|result| result.decode::<geoip2::City>())
+        {
+            Ok(city) => city?,
+            Err(error) => {
+                tracing::warn!("GeoIP lookup failed for {}: {}", ip, error);
+                return None;
+            }
+        };
+
+        let location = GeoLocation::new(
+            city.country.iso_code.map(str::to_string),
+            city.country.names.english.map(str::to_string),
+            city.subdivisions
+                .first()
+                .and_then(|region| region.names.english)
+                .map(str::to_string),
+            city.city.names.english.map(str::to_string),
+        );
+
+        (!location.is_empty()).then_some(location)
+    }
+}
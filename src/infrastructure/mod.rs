@@ -1,5 +1,6 @@
 pub mod cache;
 pub mod database;
+pub mod geoip;
 pub mod securtity;
 pub mod server;
 pub mod telemetry;
@@ -0,0 +1,129 @@
+use std::net::IpAddr;
+
+use actix_web::{HttpRequest, web::Data};
+use ipnetwork::{IpNetwork, IpNetworkError};
+
+/// Works out the client address of a request. `X-Forwarded-For` is only
+/// believed when the connection comes from one of the trusted proxies,
+/// otherwise anyone could pick the address they are counted and limited as.
+#[derive(Debug, Clone, Default)]
+pub struct ClientIpResolver {
+    trusted_proxies: Vec<IpNetwork>,
+}
+
+impl ClientIpResolver {
+    /// `trusted_proxies` is a comma separated list of addresses or CIDR ranges.
+    pub fn new(trusted_proxies: &str) -> Result<Self, IpNetworkError> {
+        let trusted_proxies = trusted_proxies
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { trusted_proxies })
+    }
+
+    /// Resolves with the resolver registered as app data, falling back to the peer address.
+    pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+        match req.app_data::<Data<ClientIpResolver>>() {
+            Some(resolver) => resolver.resolve(req),
+            None => req.peer_addr().map(|address| address.ip()),
+        }
+    }
+
+    pub fn resolve(&self, req: &HttpRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr()?.ip();
+        let forwarded_for = req
+            .headers()
+            .get_all("x-forwarded-for")
+            .filter_map(|value| value.to_str().ok());
+
+        Some(self.resolve_from(peer, forwarded_for))
+    }
+
+    /// Walks the forwarded chain from the nearest hop, stopping at the first
+    /// address that isn't a trusted proxy.
+    fn resolve_from<'a>(
+        &self,
+        peer: IpAddr,
+        forwarded_for: impl Iterator<Item = &'a str>,
+    ) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = forwarded_for.flat_map(|value| value.split(',')).collect();
+        let mut client = peer;
+
+        for hop in hops.into_iter().rev() {
+            let Ok(address) = hop.trim().parse::<IpAddr>() else {
+                break;
+            };
+
+            client = address;
+
+            if !self.is_trusted(address) {
+                break;
+            }
+        }
+
+        client
+    }
+
+    fn is_trusted(&self, address: IpAddr) -> bool {
+        self.trusted_proxies
+            .iter()
+            .any(|network| network.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn ignores_forwarded_headers_from_untrusted_peers() {
+        let resolver = ClientIpResolver::new("10.0.0.0/8").unwrap();
+
+        let client = resolver.resolve_from(ip("203.0.113.9"), ["1.2.3.4"].into_iter());
+
+        assert_eq!(client, ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn takes_the_nearest_untrusted_forwarded_address() {
+        let resolver = ClientIpResolver::new("10.0.0.0/8, 192.168.1.1").unwrap();
+
+        let client = resolver.resolve_from(
+            ip("10.0.0.2"),
+            ["6.6.6.6, 198.51.100.7", "192.168.1.1"].into_iter(),
+        );
+
+        assert_eq!(client, ip("198.51.100.7"));
+    }
+
+    #[test]
+    fn falls_back_to_the_last_trusted_hop() {
+        let resolver = ClientIpResolver::new("10.0.0.0/8").unwrap();
+
+        assert_eq!(
+            resolver.resolve_from(ip("10.0.0.2"), std::iter::empty()),
+            ip("10.0.0.2")
+        );
+        assert_eq!(
+            resolver.resolve_from(ip("10.0.0.2"), ["garbage, 10.0.0.3"].into_iter()),
+            ip("10.0.0.3")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_proxy_lists() {
+        assert!(ClientIpResolver::new("10.0.0.0/33").is_err());
+        assert!(ClientIpResolver::new("").is_ok());
+    }
+}
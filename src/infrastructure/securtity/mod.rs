@@ -1,3 +1,7 @@
+mod client_ip;
+mod rate_limit_middleware;
 mod rate_limiter;
 
+pub use client_ip::*;
+pub use rate_limit_middleware::*;
 pub use rate_limiter::*;
@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    Error,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::{
+        StatusCode,
+        header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER},
+    },
+    web::Data,
+};
+use serde_json::{Value, json};
+
+use crate::{
+    configuration::{RateLimitBackend, RateLimitPolicy, RateLimitSettings},
+    infrastructure::{
+        cache::{error::CacheError, rate_limiter_cache::RateLimitCache},
+        securtity::{ClientIpResolver, RateLimitDecision, RateLimiter},
+        server::ApiResponse,
+    },
+};
+
+enum RateLimitStore {
+    Memory(RateLimiter),
+    Redis(RateLimitCache),
+}
+
+/// Shared rate limiting state, registered as app data and looked up by [`RateLimit`].
+pub struct RateLimitGuard {
+    store: RateLimitStore,
+    policies: HashMap<String, RateLimitPolicy>,
+}
+
+impl RateLimitGuard {
+    pub async fn new(settings: &RateLimitSettings, redis_url: &str) -> Result<Self, CacheError> {
+        let store = match settings.backend {
+            RateLimitBackend::Memory => RateLimitStore::Memory(RateLimiter::new()),
+            RateLimitBackend::Redis => RateLimitStore::Redis(RateLimitCache::new(redis_url).await?),
+        };
+
+        Ok(Self {
+            store,
+            policies: settings.policies.clone(),
+        })
+    }
+
+    /// Returns `None` when the policy is not configured or the backend is
+    /// unavailable, in which case the request is let through.
+    async fn check(&self, policy_name: &str, client_id: &str) -> Option<RateLimitDecision> {
+        let policy = self.policies.get(policy_name)?;
+        let key = format!("{}:{}", policy_name, client_id);
+
+        match &self.store {
+            RateLimitStore::Memory(limiter) => {
+                Some(limiter.check(key, policy.limit as usize, policy.window()))
+            }
+            RateLimitStore::Redis(cache) => {
+                match cache
+                    .increment_request(&key, policy.limit, policy.window())
+                    .await
+                {
+                    Ok(decision) => Some(decision),
+                    Err(error) => {
+                        tracing::warn!("Rate limit backend unavailable: {}", error);
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Middleware applying a named policy from `rate_limit.policies` to a resource.
+///
+/// Clients are identified by their IP address, see [`ClientIpResolver`].
+pub struct RateLimit {
+    policy: &'static str,
+}
+
+impl RateLimit {
+    pub fn policy(name: &'static str) -> Self {
+        Self { policy: name }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            policy: self.policy,
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    policy: &'static str,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let policy = self.policy;
+
+        Box::pin(async move {
+            let Some(guard) = req.app_data::<Data<RateLimitGuard>>().cloned() else {
+                return service.call(req).await.map(|res| res.map_into_left_body());
+            };
+
+            let client_id = ClientIpResolver::client_ip(req.request())
+                .map_or_else(|| String::from("unknown"), |address| address.to_string());
+
+            let Some(decision) = guard.check(policy, &client_id).await else {
+                return service.call(req).await.map(|res| res.map_into_left_body());
+            };
+
+            if !decision.allowed {
+                let retry_after = decision.reset_after.as_secs().max(1);
+                let mut response = ApiResponse::<Value>::fail(
+                    json!({"message": format!("Too many requests, retry in {} seconds", retry_after)}),
+                    StatusCode::TOO_MANY_REQUESTS,
+                );
+
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+                insert_rate_limit_headers(response.headers_mut(), &decision);
+
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+
+            let mut response = service.call(req).await?;
+            insert_rate_limit_headers(response.headers_mut(), &decision);
+
+            Ok(response.map_into_left_body())
+        })
+    }
+}
+
+fn insert_rate_limit_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-limit"),
+        HeaderValue::from(decision.limit),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-remaining"),
+        HeaderValue::from(decision.remaining),
+    );
+    headers.insert(
+        HeaderName::from_static("x-ratelimit-reset"),
+        HeaderValue::from(decision.reset_after.as_secs()),
+    );
+}
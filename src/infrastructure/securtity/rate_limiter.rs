@@ -11,37 +11,151 @@ struct RateLimiterState {
     window: Duration,
 }
 
+/// Outcome of a single rate limit check, used to build the `X-RateLimit-*` headers.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_after: Duration,
+}
+
+/// How often clients idle for longer than their window are dropped, so the
+/// map doesn't grow with every address ever seen.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct RateLimiterStates {
+    clients: HashMap<String, RateLimiterState>,
+    last_sweep: Instant,
+}
+
 pub struct RateLimiter {
-    states: Arc<Mutex<HashMap<String, RateLimiterState>>>,
+    states: Arc<Mutex<RateLimiterStates>>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RateLimiter {
     pub fn new() -> Self {
         Self {
-            states: Arc::new(Mutex::new(HashMap::new())),
+            states: Arc::new(Mutex::new(RateLimiterStates {
+                clients: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
         }
     }
 
     pub fn allow_request(&self, client_id: String, limit: usize, window: Duration) -> bool {
+        self.check(client_id, limit, window).allowed
+    }
+
+    pub fn check(&self, client_id: String, limit: usize, window: Duration) -> RateLimitDecision {
+        self.check_at(client_id, limit, window, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        client_id: String,
+        limit: usize,
+        window: Duration,
+        now: Instant,
+    ) -> RateLimitDecision {
         let mut states = self.states.lock().unwrap();
 
-        let state = states.entry(client_id).or_insert(RateLimiterState {
+        if now.duration_since(states.last_sweep) >= SWEEP_INTERVAL {
+            states.clients.retain(|_, state| {
+                state
+                    .requests
+                    .last()
+                    .is_some_and(|&last| now.duration_since(last) < state.window)
+            });
+            states.last_sweep = now;
+        }
+
+        let state = states.clients.entry(client_id).or_insert(RateLimiterState {
             requests: Vec::new(),
             limit,
             window,
         });
 
-        let now = Instant::now();
-
         state
             .requests
             .retain(|&req| now.duration_since(req) < state.window);
 
-        if state.requests.len() >= state.limit {
-            false
-        } else {
+        let allowed = state.requests.len() < state.limit;
+
+        if allowed {
             state.requests.push(now);
-            true
         }
+
+        let reset_after = state
+            .requests
+            .first()
+            .map(|&oldest| state.window.saturating_sub(now.duration_since(oldest)))
+            .unwrap_or(state.window);
+
+        RateLimitDecision {
+            allowed,
+            limit: state.limit as u32,
+            remaining: state.limit.saturating_sub(state.requests.len()) as u32,
+            reset_after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_requests_over_the_limit_within_the_window() {
+        let limiter = RateLimiter::new();
+        let window = Duration::from_secs(60);
+
+        let first = limiter.check("127.0.0.1".into(), 2, window);
+        let second = limiter.check("127.0.0.1".into(), 2, window);
+        let third = limiter.check("127.0.0.1".into(), 2, window);
+
+        assert!(first.allowed && second.allowed);
+        assert_eq!(first.remaining, 1);
+        assert!(!third.allowed);
+        assert_eq!(third.remaining, 0);
+        assert!(third.reset_after <= window);
+    }
+
+    #[test]
+    fn tracks_clients_independently() {
+        let limiter = RateLimiter::new();
+        let window = Duration::from_secs(60);
+
+        assert!(limiter.allow_request("a".into(), 1, window));
+        assert!(!limiter.allow_request("a".into(), 1, window));
+        assert!(limiter.allow_request("b".into(), 1, window));
+    }
+
+    #[test]
+    fn sweeps_idle_clients_periodically() {
+        let limiter = RateLimiter::new();
+        let window = Duration::from_secs(10);
+        let start = Instant::now();
+
+        limiter.check_at("idle".into(), 5, window, start);
+        limiter.check_at(
+            "active".into(),
+            5,
+            window,
+            start + SWEEP_INTERVAL - window / 2,
+        );
+        assert_eq!(limiter.states.lock().unwrap().clients.len(), 2);
+
+        limiter.check_at("active".into(), 5, window, start + SWEEP_INTERVAL);
+
+        let states = limiter.states.lock().unwrap();
+        assert!(states.clients.contains_key("active"));
+        assert!(!states.clients.contains_key("idle"));
     }
 }
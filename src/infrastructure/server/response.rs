@@ -2,18 +2,40 @@ use actix_web::{HttpResponse, http::StatusCode};
 use serde::Serialize;
 use serde_json::Value;
 
+/// Pagination details for list responses, `next_cursor` is absent on the last page.
+#[derive(Debug, Serialize)]
+pub struct PageMeta {
+    pub limit: i64,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(tag = "status", rename_all = "lowercase")]
 pub enum ApiResponse<T> {
-    Success { data: T },
-    Fail { data: Value },
-    Error { message: String },
+    Success {
+        data: T,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        meta: Option<PageMeta>,
+    },
+    Fail {
+        data: Value,
+    },
+    Error {
+        message: String,
+    },
     Redirect,
 }
 
 impl<T: Serialize> ApiResponse<T> {
     pub fn success(data: T) -> HttpResponse {
-        HttpResponse::Ok().json(Self::Success { data })
+        HttpResponse::Ok().json(Self::Success { data, meta: None })
+    }
+
+    pub fn success_with_meta(data: T, meta: PageMeta) -> HttpResponse {
+        HttpResponse::Ok().json(Self::Success {
+            data,
+            meta: Some(meta),
+        })
     }
 
     pub fn success_with_no_content() -> HttpResponse {
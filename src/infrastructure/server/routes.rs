@@ -1,13 +1,22 @@
+use crate::features::api_keys::handlers::ApiKeyHandler;
+use crate::features::dashboard::handlers::DashboardHandler;
 use crate::features::urls::handlers::UrlHandler;
+use crate::infrastructure::securtity::RateLimit;
 use crate::infrastructure::server::ApiResponse;
 use actix_web::{
     Error, HttpRequest,
-    error::{InternalError, JsonPayloadError},
+    error::{InternalError, JsonPayloadError, QueryPayloadError},
+    guard,
     http::StatusCode,
-    web::{self, JsonConfig, ServiceConfig, delete, get, patch, post, scope},
+    web::{
+        self, JsonConfig, QueryConfig, ServiceConfig, delete, get, patch, post, resource, scope,
+    },
 };
 use serde_json::{Value, json};
 
+/// Body size limit for bulk link creation, other routes keep the 4 KB default.
+const BATCH_PAYLOAD_LIMIT: usize = 8 * 1024 * 1024;
+
 pub struct Routes;
 
 impl Routes {
@@ -17,18 +26,97 @@ impl Routes {
                 .limit(4096) // limit payload size
                 .error_handler(json_error_handler),
         )
+        .app_data(QueryConfig::default().error_handler(query_error_handler))
         .service(
-            scope("/api").service(
-                scope("/shorten")
-                    .route("", post().to(UrlHandler::create_short_url))
-                    .route("/{code}", get().to(UrlHandler::retreive_url_by_short_code))
-                    .route(
-                        "/{code}/stats",
-                        get().to(UrlHandler::fetch_short_code_stats),
-                    )
-                    .route("/{code}", patch().to(UrlHandler::update_url_by_short_code))
-                    .route("/{code}", delete().to(UrlHandler::delete_url_by_short_code)),
-            ),
+            scope("/api")
+                .service(
+                    scope("/admin/keys")
+                        .route("", post().to(ApiKeyHandler::create_api_key))
+                        .route("", get().to(ApiKeyHandler::list_api_keys))
+                        .route("/{id}", delete().to(ApiKeyHandler::revoke_api_key)),
+                )
+                .route("/trash", get().to(UrlHandler::list_trash))
+                .service(
+                    scope("/stats")
+                        .route("/export", get().to(UrlHandler::export_logs))
+                        .route("/summary", get().to(DashboardHandler::fetch_summary))
+                        .route("/top-links", get().to(DashboardHandler::fetch_top_links))
+                        .route(
+                            "/top-domains",
+                            get().to(DashboardHandler::fetch_top_domains),
+                        )
+                        .route("/new-links", get().to(DashboardHandler::fetch_new_links)),
+                )
+                .service(
+                    scope("/shorten")
+                        .service(
+                            resource("")
+                                .guard(guard::Post())
+                                .wrap(RateLimit::policy("create"))
+                                .to(UrlHandler::create_short_url),
+                        )
+                        .route("", get().to(UrlHandler::list_urls))
+                        .service(
+                            resource("/batch")
+                                .guard(guard::Post())
+                                .app_data(
+                                    JsonConfig::default()
+                                        .limit(BATCH_PAYLOAD_LIMIT)
+                                        .error_handler(json_error_handler),
+                                )
+                                .wrap(RateLimit::policy("batch"))
+                                .to(UrlHandler::create_short_urls),
+                        )
+                        .service(
+                            resource("/{code}")
+                                .guard(guard::Get())
+                                .wrap(RateLimit::policy("redirect"))
+                                .to(UrlHandler::retreive_url_by_short_code),
+                        )
+                        .route(
+                            "/{code}/stats",
+                            get().to(UrlHandler::fetch_short_code_stats),
+                        )
+                        .route(
+                            "/{code}/stats/logs",
+                            get().to(UrlHandler::fetch_short_code_logs),
+                        )
+                        .route(
+                            "/{code}/stats/clients",
+                            get().to(UrlHandler::fetch_short_code_clients),
+                        )
+                        .route(
+                            "/{code}/stats/countries",
+                            get().to(UrlHandler::fetch_short_code_countries),
+                        )
+                        .route(
+                            "/{code}/stats/referrers",
+                            get().to(UrlHandler::fetch_short_code_referrers),
+                        )
+                        .route(
+                            "/{code}/stats/export",
+                            get().to(UrlHandler::export_short_code_logs),
+                        )
+                        .route(
+                            "/{code}/stats/live",
+                            get().to(UrlHandler::stream_short_code_clicks),
+                        )
+                        .route(
+                            "/{code}/stats/timeseries",
+                            get().to(UrlHandler::fetch_short_code_timeseries),
+                        )
+                        .route("/{code}/history", get().to(UrlHandler::fetch_url_history))
+                        .route(
+                            "/{code}/rollback/{revision}",
+                            post().to(UrlHandler::rollback_url_by_short_code),
+                        )
+                        .route(
+                            "/{code}/restore",
+                            post().to(UrlHandler::restore_url_by_short_code),
+                        )
+                        .route("/{code}", patch().to(UrlHandler::update_url_by_short_code))
+                        .route("/{code}", delete().to(UrlHandler::delete_url_by_short_code)),
+                ),
         )
         .default_service(web::route().to(|req: HttpRequest| async move {
             ApiResponse::<&str>::fail(
@@ -41,38 +129,39 @@ impl Routes {
 
 fn json_error_handler(error: JsonPayloadError, _req: &HttpRequest) -> Error {
     let error_message = match &error {
-        JsonPayloadError::ContentType => format!("Content body must be a json object"),
+        JsonPayloadError::ContentType => "Content body must be a json object".to_string(),
         JsonPayloadError::Deserialize(json_error) => json_deserialization(json_error),
-        JsonPayloadError::Payload(_) => format!("Empty or invalid payload"),
-        _ => format!("Invalid JSON payload"),
+        JsonPayloadError::Payload(_) => "Empty or invalid payload".to_string(),
+        _ => "Invalid JSON payload".to_string(),
     };
 
     let response = ApiResponse::<&str>::fail(Value::from(error_message), StatusCode::BAD_REQUEST);
     InternalError::from_response(error, response).into()
 }
 
+fn query_error_handler(error: QueryPayloadError, _req: &HttpRequest) -> Error {
+    let response = ApiResponse::<&str>::fail(
+        Value::from(format!("Invalid query string: {}", error)),
+        StatusCode::BAD_REQUEST,
+    );
+    InternalError::from_response(error, response).into()
+}
+
 fn json_deserialization(json_error: &serde_json::Error) -> String {
     let detail = json_error.to_string();
 
     println!("detailer: {}", detail);
 
     if let Some(message) = extract_field_from_error(&detail) {
-        format!("{}", message)
+        message
     } else {
         format!("JSON deserialization error: {}", detail)
     }
 }
 
 fn extract_field_from_error(message: &str) -> Option<String> {
-    let splitter: Vec<&str> = message.split("at").collect();
-
-    if splitter.len() >= 2 {
-        let value = splitter.first();
-
-        if let Some(string) = value {
-            return Some(string.trim().to_string());
-        }
-    }
-
-    None
+    // serde_json appends the position as " at line X column Y"
+    message
+        .rsplit_once(" at line ")
+        .map(|(value, _)| value.trim().to_string())
 }
@@ -1,5 +1,5 @@
 use actix_web::dev::Server;
-use actix_web::middleware::{NormalizePath, TrailingSlash};
+use actix_web::middleware::{NormalizePath, TrailingSlash, from_fn};
 use actix_web::web::{Data, get};
 use actix_web::{App, HttpServer};
 use std::net::TcpListener;
@@ -7,13 +7,27 @@ use std::sync::Arc;
 use tracing_actix_web::TracingLogger;
 
 use crate::configuration::Settings;
-use crate::features::url_stats::queue::StatsProcessor;
+use crate::features::api_keys::repository::ApiKeyRepository;
+use crate::features::api_keys::service::ApiKeyService;
+use crate::features::dashboard::repository::DashboardRepository;
+use crate::features::dashboard::service::DashboardService;
+use crate::features::url_stats::jobs::{StatsRetentionJob, StatsRollupJob};
+use crate::features::url_stats::live::LiveClickHub;
+use crate::features::url_stats::queue::{PgStatsQueue, StatsProcessor};
 use crate::features::url_stats::repository::UrlStatsRepository;
 use crate::features::url_stats::service::UrlStatsService;
-use crate::features::urls::handlers::health_check;
-use crate::features::urls::repository::UrlRepository;
+use crate::features::urls::generators::{CodeGenerator, ShortCodePool};
+use crate::features::urls::handlers::{health_check, metrics};
+use crate::features::urls::jobs::{ShortCodePoolJob, TrashPurgeJob};
+use crate::features::urls::repository::{
+    ShortCodeCounterRepository, ShortCodePoolRepository, UrlRepository,
+};
 use crate::features::urls::service::UrlService;
+use crate::infrastructure::cache::redis_cache::RedisCache;
 use crate::infrastructure::database::connection::DatabasePool;
+use crate::infrastructure::geoip::geoip_resolver::GeoIpResolver;
+use crate::infrastructure::securtity::{ClientIpResolver, RateLimitGuard};
+use crate::infrastructure::telemetry::{ApplicationMetrics, track_request_metrics};
 
 use super::routes::Routes;
 
@@ -21,11 +35,15 @@ use super::routes::Routes;
 pub struct AppServices {
     pub url_service: Arc<UrlService<UrlRepository>>,
     pub url_stats_service: Arc<UrlStatsService<UrlStatsRepository>>,
+    pub api_key_service: Arc<ApiKeyService<ApiKeyRepository>>,
+    pub dashboard_service: Arc<DashboardService<DashboardRepository>>,
+    pub live_clicks: Arc<LiveClickHub>,
+    pub metrics: Arc<ApplicationMetrics>,
 }
 
 #[derive(Clone)]
 pub struct QueueProcessor {
-    pub stats_processor: StatsProcessor,
+    pub stats_processor: StatsProcessor<PgStatsQueue>,
 }
 
 pub struct WebServer {
@@ -37,12 +55,13 @@ impl WebServer {
     pub async fn build(
         config: Settings,
         database_pool: DatabasePool,
+        redis_cache: RedisCache,
     ) -> Result<WebServer, anyhow::Error> {
         let address = format!("{}:{}", config.application.host, config.application.port);
         let listener = TcpListener::bind(address)?;
         let port = listener.local_addr().unwrap().port();
 
-        let server = Self::run(listener, database_pool).await?;
+        let server = Self::run(listener, config, database_pool, redis_cache).await?;
 
         Ok(Self {
             _port: port,
@@ -52,37 +71,122 @@ impl WebServer {
 
     async fn run(
         listener: TcpListener,
+        config: Settings,
         database_pool: DatabasePool,
+        redis_cache: RedisCache,
     ) -> Result<Server, anyhow::Error> {
         // Create repositories
         let db_connection = Arc::new(database_pool);
+        let cache = Arc::new(redis_cache);
+        let application_metrics = Arc::new(ApplicationMetrics::new());
         let url_repository = Arc::new(UrlRepository::new(db_connection.clone()));
+        let short_code_counter = Arc::new(ShortCodeCounterRepository::new(db_connection.clone()));
+        let short_code_pool_repository =
+            Arc::new(ShortCodePoolRepository::new(db_connection.clone()));
         let url_stats_repository = Arc::new(UrlStatsRepository::new(db_connection.clone()));
+        let api_key_repository = Arc::new(ApiKeyRepository::new(db_connection.clone()));
+        let dashboard_repository = Arc::new(DashboardRepository::new(db_connection.clone()));
 
         // Create services
-        let url_service = Arc::new(UrlService::new(url_repository));
-        let url_stats_service = Arc::new(UrlStatsService::new(url_stats_repository));
+        let short_code_pool = config.short_code_pool.enabled.then(|| {
+            ShortCodePool::new(
+                short_code_pool_repository,
+                application_metrics.clone(),
+                config.short_code_pool.clone(),
+            )
+        });
+        let code_generator = Arc::new(CodeGenerator::new(
+            &config.short_codes,
+            short_code_counter,
+            short_code_pool,
+        )?);
+        let url_service = Arc::new(UrlService::new(
+            url_repository,
+            cache.clone(),
+            config.redis.url_cache_ttl(),
+            application_metrics.clone(),
+            config.links.dedupe,
+            config.trash.clone(),
+            code_generator.clone(),
+        ));
+        let geoip = config
+            .geoip
+            .database_path()
+            .map(GeoIpResolver::open)
+            .transpose()?
+            .map(Arc::new);
+        let url_stats_service = Arc::new(UrlStatsService::new(
+            url_stats_repository,
+            geoip,
+            config.privacy.clone(),
+            cache.clone(),
+            config.unique_visitors.clone(),
+        ));
+        let dashboard_service = Arc::new(DashboardService::new(
+            dashboard_repository,
+            cache.clone(),
+            config.dashboard.leaderboard_ttl(),
+        ));
+        let api_key_service = Arc::new(ApiKeyService::new(
+            api_key_repository,
+            config.auth.admin_token.as_ref(),
+        ));
 
         // Task Queues
-        let stats_processor = StatsProcessor::new(100, url_stats_service.clone());
+        let live_clicks = Arc::new(LiveClickHub::new(&config.live_stats));
+        let stats_queue = Arc::new(PgStatsQueue::new(db_connection.clone()));
+        let stats_processor = StatsProcessor::new(
+            stats_queue.clone(),
+            config.stats_queue.clone(),
+            url_stats_service.clone(),
+            live_clicks.clone(),
+            application_metrics.clone(),
+        );
+
+        // Background jobs
+        StatsRollupJob::spawn(url_stats_service.clone(), config.stats_rollup.clone());
+        StatsRetentionJob::spawn(
+            url_stats_service.clone(),
+            stats_queue,
+            config.privacy.clone(),
+        );
+        TrashPurgeJob::spawn(url_service.clone(), config.trash.clone());
+        ShortCodePoolJob::spawn(code_generator, config.short_code_pool.clone());
 
         // App State
         let services = AppServices {
             url_service,
             url_stats_service,
+            api_key_service,
+            dashboard_service,
+            live_clicks,
+            metrics: application_metrics.clone(),
         };
 
         // App Queue
         let processors = QueueProcessor { stats_processor };
 
+        // Rate limiting
+        let rate_limit_guard =
+            Data::new(RateLimitGuard::new(&config.rate_limit, &config.redis.uri).await?);
+        let client_ip_resolver =
+            Data::new(ClientIpResolver::new(&config.application.trusted_proxies)?);
+
+        let metrics_data = Data::from(application_metrics);
+
         let server = HttpServer::new(move || {
             App::new()
+                .wrap(from_fn(track_request_metrics))
                 .wrap(TracingLogger::default())
                 .wrap(NormalizePath::new(TrailingSlash::Trim))
                 .route("healthz", get().to(health_check))
+                .route("metrics", get().to(metrics))
                 .configure(Routes::configure_routes)
                 .app_data(Data::new(services.clone()))
                 .app_data(Data::new(processors.clone()))
+                .app_data(rate_limit_guard.clone())
+                .app_data(client_ip_resolver.clone())
+                .app_data(metrics_data.clone())
         })
         .listen(listener)?
         .run();
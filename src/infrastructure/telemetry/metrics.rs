@@ -1,10 +1,35 @@
-use prometheus::{Encoder, Gauge, IntCounter, Registry, TextEncoder};
+use std::time::Instant;
+
+use actix_web::{
+    Error,
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web::Data,
+};
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounter, IntGauge, Registry, TextEncoder,
+};
 
 pub struct ApplicationMetrics {
     registry: Registry,
     url_creation_counter: IntCounter,
     url_access_counter: IntCounter,
     active_urls_gauge: Gauge,
+    stats_dropped_counter: IntCounter,
+    stats_failed_counter: IntCounter,
+    stats_dead_lettered_counter: IntCounter,
+    short_code_pool_depth_gauge: IntGauge,
+    short_code_pool_refilled_counter: IntCounter,
+    short_code_pool_claimed_counter: IntCounter,
+    short_code_pool_missed_counter: IntCounter,
+    http_request_duration: HistogramVec,
+}
+
+impl Default for ApplicationMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ApplicationMetrics {
@@ -22,6 +47,57 @@ impl ApplicationMetrics {
         let active_urls_gauge = Gauge::new("active_urls", "Number of active short URLs")
             .expect("Failed to create metric");
 
+        let stats_dropped_counter = IntCounter::new(
+            "stats_events_dropped_total",
+            "Total number of stats events dropped because they couldn't be queued",
+        )
+        .expect("Failed to create metric");
+
+        let stats_failed_counter = IntCounter::new(
+            "stats_events_failed_total",
+            "Total number of stats events that failed to be recorded",
+        )
+        .expect("Failed to create metric");
+
+        let stats_dead_lettered_counter = IntCounter::new(
+            "stats_events_dead_lettered_total",
+            "Total number of stats events moved to the dead-letter store",
+        )
+        .expect("Failed to create metric");
+
+        let short_code_pool_depth_gauge = IntGauge::new(
+            "short_code_pool_depth",
+            "Number of unused short codes in the pool, as of the last refill check",
+        )
+        .expect("Failed to create metric");
+
+        let short_code_pool_refilled_counter = IntCounter::new(
+            "short_code_pool_refilled_total",
+            "Total number of short codes added to the pool",
+        )
+        .expect("Failed to create metric");
+
+        let short_code_pool_claimed_counter = IntCounter::new(
+            "short_code_pool_claimed_total",
+            "Total number of short codes claimed from the pool",
+        )
+        .expect("Failed to create metric");
+
+        let short_code_pool_missed_counter = IntCounter::new(
+            "short_code_pool_missed_total",
+            "Total number of short codes generated on the spot because the pool ran dry",
+        )
+        .expect("Failed to create metric");
+
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route and status",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("Failed to create metric");
+
         registry
             .register(Box::new(url_creation_counter.clone()))
             .unwrap();
@@ -31,12 +107,44 @@ impl ApplicationMetrics {
         registry
             .register(Box::new(active_urls_gauge.clone()))
             .unwrap();
+        registry
+            .register(Box::new(stats_dropped_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stats_failed_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(stats_dead_lettered_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(short_code_pool_depth_gauge.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(short_code_pool_refilled_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(short_code_pool_claimed_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(short_code_pool_missed_counter.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(http_request_duration.clone()))
+            .unwrap();
 
         Self {
             registry,
             url_creation_counter,
             url_access_counter,
             active_urls_gauge,
+            stats_dropped_counter,
+            stats_failed_counter,
+            stats_dead_lettered_counter,
+            short_code_pool_depth_gauge,
+            short_code_pool_refilled_counter,
+            short_code_pool_claimed_counter,
+            short_code_pool_missed_counter,
+            http_request_duration,
         }
     }
 
@@ -44,6 +152,10 @@ impl ApplicationMetrics {
         self.url_creation_counter.inc();
     }
 
+    pub fn increment_url_creation_by(&self, count: u64) {
+        self.url_creation_counter.inc_by(count);
+    }
+
     pub fn increment_url_access(&self) {
         self.url_access_counter.inc();
     }
@@ -52,12 +164,84 @@ impl ApplicationMetrics {
         self.active_urls_gauge.set(count);
     }
 
-    pub fn export_metrics(&self) {
+    pub fn increment_stats_dropped(&self) {
+        self.stats_dropped_counter.inc();
+    }
+
+    pub fn increment_stats_dropped_by(&self, count: u64) {
+        self.stats_dropped_counter.inc_by(count);
+    }
+
+    pub fn increment_stats_failed(&self) {
+        self.stats_failed_counter.inc();
+    }
+
+    pub fn increment_stats_dead_lettered(&self) {
+        self.stats_dead_lettered_counter.inc();
+    }
+
+    pub fn set_short_code_pool_depth(&self, depth: i64) {
+        self.short_code_pool_depth_gauge.set(depth);
+    }
+
+    pub fn increment_short_code_pool_depth_by(&self, count: i64) {
+        self.short_code_pool_depth_gauge.add(count);
+    }
+
+    pub fn decrement_short_code_pool_depth_by(&self, count: i64) {
+        self.short_code_pool_depth_gauge.sub(count);
+    }
+
+    pub fn increment_short_code_pool_refilled_by(&self, count: u64) {
+        self.short_code_pool_refilled_counter.inc_by(count);
+    }
+
+    pub fn increment_short_code_pool_claimed_by(&self, count: u64) {
+        self.short_code_pool_claimed_counter.inc_by(count);
+    }
+
+    pub fn increment_short_code_pool_missed_by(&self, count: u64) {
+        self.short_code_pool_missed_counter.inc_by(count);
+    }
+
+    pub fn observe_http_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.http_request_duration
+            .with_label_values(&[method, route, &status.to_string()])
+            .observe(seconds);
+    }
+
+    pub fn export_metrics(&self) -> Result<String, anyhow::Error> {
         let encoder = TextEncoder::new();
         let metric_families = self.registry.gather();
         let mut buffer = Vec::new();
 
-        encoder.encode(&metric_families, &mut buffer).unwrap();
-        String::from_utf8(buffer).unwrap();
+        encoder.encode(&metric_families, &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
     }
 }
+
+/// Records the latency of every request, labelled with the matched route
+/// pattern (not the raw path) to keep label cardinality bounded.
+pub async fn track_request_metrics(
+    metrics: Data<ApplicationMetrics>,
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let started = Instant::now();
+    let response = next.call(req).await?;
+
+    // The route is only resolved once the request went through the router
+    let request = response.request();
+    let route = request
+        .match_pattern()
+        .unwrap_or_else(|| String::from("unmatched"));
+
+    metrics.observe_http_request(
+        request.method().as_str(),
+        &route,
+        response.status().as_u16(),
+        started.elapsed().as_secs_f64(),
+    );
+
+    Ok(response)
+}
@@ -1,4 +1,5 @@
 mod metrics;
+#[allow(clippy::module_inception)]
 mod telemetry;
 
 pub use metrics::*;
@@ -12,9 +12,9 @@ async fn main() -> Result<()> {
     Telemetry::init_subscriber(&config.application.name, "info".into(), std::io::stdout);
 
     let pool = DatabasePool::new(&config.database).await?;
-    let _redis = RedisCache::new(&config.redis.uri);
+    let redis = RedisCache::new(&config.redis.uri).await?;
 
-    let server = WebServer::build(config, pool).await?;
+    let server = WebServer::build(config, pool, redis).await?;
     server.run_until_stopped().await?;
 
     Ok(())